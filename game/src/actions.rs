@@ -1,6 +1,14 @@
 use bevy::{
     app::Plugin,
-    ecs::{component::Component, entity::Entity, event::EntityEvent},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EntityEvent,
+        schedule::{
+            IntoScheduleConfigs,
+            common_conditions::{not, resource_exists},
+        },
+    },
     math::I16Vec2,
     state::state::OnEnter,
 };
@@ -11,12 +19,28 @@ use crate::{
             apply_discard_cards, apply_draw_cards, apply_heal, apply_mill, apply_modify_stats,
             apply_move_creature, apply_return_to_hand,
         },
-        execute::{drive_abilities, on_card_played, on_turn_end},
-        hooks::HookEvent,
+        execute::{
+            drive_abilities, fire_delayed_abilities, on_card_moved, on_card_played,
+            on_cards_drawn, on_creature_attacked, on_creature_damaged, on_creature_destroyed,
+            on_decked_out, on_trap_revealed, on_turn_end, on_turn_start, tick_delayed_abilities,
+        },
+        hooks::{HookEvent, register_hooks, reset_used_hooks},
+        stack::{
+            EffectStack, PassPriority, Priority, pass_priority, pop_ability, push_ability,
+            sync_acting_player,
+        },
         value_source::StatModifier,
     },
     board::effect::EffectType,
-    def::trigger::AbilityDef,
+    card::modifiers::{StatDuration, expire_end_of_turn_modifiers, tick_stat_modifiers},
+    def::trigger::{AbilityDef, HookDef},
+    events::{
+        CardMoved, CardsDrawn, CreatureAttacked, CreatureDamaged, CreatureDestroyed, DeckedOut,
+        TrapRevealed,
+    },
+    phases::Phase,
+    rules::Ruleset,
+    turn_controller::{TurnEnding, TurnPhase, TurnState},
 };
 
 pub mod appliers;
@@ -24,6 +48,7 @@ pub mod conditions;
 pub mod execute;
 pub mod hooks;
 pub mod spell_speed;
+pub mod stack;
 pub mod targeting;
 pub mod timing;
 pub mod value_source;
@@ -82,27 +107,50 @@ impl MoveCreature {
     }
 }
 
-impl HookEvent for MoveCreature {}
+impl HookEvent for MoveCreature {
+    const KIND: HookDef = HookDef::Moved;
+}
 
+/// The turn of the player it targets ends. Nothing applies it; it is
+/// triggered for the `TurnEnded` hooks of that player's cards.
 #[derive(EntityEvent)]
 pub struct EndTurn(pub Entity);
 
-impl HookEvent for EndTurn {}
+impl HookEvent for EndTurn {
+    const KIND: HookDef = HookDef::TurnEnded;
+}
 
 // Atomic effects
 #[derive(EntityEvent)]
 pub struct DealDamage {
     pub amount: u16,
     pub entity: Entity,
+    /// The creature or card dealing the damage, if any.
+    pub source: Option<Entity>,
 }
 
 impl DealDamage {
     pub fn new(amount: u16, entity: Entity) -> Self {
-        Self { amount, entity }
+        Self {
+            amount,
+            entity,
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
     }
 }
 
-impl HookEvent for DealDamage {}
+impl HookEvent for DealDamage {
+    const KIND: HookDef = HookDef::Damaged;
+
+    fn amount_mut(&mut self) -> Option<&mut u16> {
+        Some(&mut self.amount)
+    }
+}
 
 #[derive(EntityEvent)]
 pub struct HealCreature {
@@ -116,7 +164,13 @@ impl HealCreature {
     }
 }
 
-impl HookEvent for HealCreature {}
+impl HookEvent for HealCreature {
+    const KIND: HookDef = HookDef::Healed;
+
+    fn amount_mut(&mut self) -> Option<&mut u16> {
+        Some(&mut self.amount)
+    }
+}
 
 #[derive(EntityEvent)]
 pub struct DrawCards {
@@ -130,7 +184,13 @@ impl DrawCards {
     }
 }
 
-impl HookEvent for DrawCards {}
+impl HookEvent for DrawCards {
+    const KIND: HookDef = HookDef::CardsDrawn;
+
+    fn amount_mut(&mut self) -> Option<&mut u16> {
+        Some(&mut self.amount)
+    }
+}
 
 #[derive(EntityEvent)]
 pub struct AddGold {
@@ -144,13 +204,21 @@ impl AddGold {
     }
 }
 
-impl HookEvent for AddGold {}
+impl HookEvent for AddGold {
+    const KIND: HookDef = HookDef::GoldGained;
+
+    fn amount_mut(&mut self) -> Option<&mut u16> {
+        Some(&mut self.amount)
+    }
+}
 
 #[derive(EntityEvent)]
 pub struct ApplyEffect {
     pub effect: EffectType,
     pub duration: u16,
     pub entity: Entity,
+    /// The card or effect laying the effect; its owner owns the effect.
+    pub source: Option<Entity>,
 }
 
 impl ApplyEffect {
@@ -159,29 +227,51 @@ impl ApplyEffect {
             effect,
             duration,
             entity,
+            source: None,
         }
     }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
+    }
 }
 
-impl HookEvent for ApplyEffect {}
+impl HookEvent for ApplyEffect {
+    const KIND: HookDef = HookDef::EffectApplied;
+}
 
 #[derive(EntityEvent)]
 pub struct DestroyCreature {
     pub entity: Entity,
+    /// The creature or card destroying it, if any.
+    pub source: Option<Entity>,
 }
 
 impl DestroyCreature {
     pub fn new(entity: Entity) -> Self {
-        Self { entity }
+        Self {
+            entity,
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
     }
 }
 
-impl HookEvent for DestroyCreature {}
+impl HookEvent for DestroyCreature {
+    const KIND: HookDef = HookDef::Destroyed;
+}
 
 #[derive(EntityEvent)]
 pub struct ModifyStats {
     pub entity: Entity,
     pub stat_modifier: StatModifier,
+    pub duration: StatDuration,
+    pub source: Option<Entity>,
 }
 
 impl ModifyStats {
@@ -189,11 +279,25 @@ impl ModifyStats {
         Self {
             entity,
             stat_modifier,
+            duration: StatDuration::Permanent,
+            source: None,
         }
     }
+
+    pub fn with_duration(mut self, duration: StatDuration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
+    }
 }
 
-impl HookEvent for ModifyStats {}
+impl HookEvent for ModifyStats {
+    const KIND: HookDef = HookDef::StatsModified;
+}
 
 #[derive(EntityEvent)]
 pub struct DiscardCards {
@@ -207,7 +311,13 @@ impl DiscardCards {
     }
 }
 
-impl HookEvent for DiscardCards {}
+impl HookEvent for DiscardCards {
+    const KIND: HookDef = HookDef::CardsDiscarded;
+
+    fn amount_mut(&mut self) -> Option<&mut u16> {
+        Some(&mut self.amount)
+    }
+}
 
 #[derive(EntityEvent)]
 pub struct ReturnToHand {
@@ -220,7 +330,9 @@ impl ReturnToHand {
     }
 }
 
-impl HookEvent for ReturnToHand {}
+impl HookEvent for ReturnToHand {
+    const KIND: HookDef = HookDef::ReturnedToHand;
+}
 
 #[derive(EntityEvent)]
 pub struct Mill {
@@ -234,7 +346,13 @@ impl Mill {
     }
 }
 
-impl HookEvent for Mill {}
+impl HookEvent for Mill {
+    const KIND: HookDef = HookDef::Milled;
+
+    fn amount_mut(&mut self) -> Option<&mut u16> {
+        Some(&mut self.amount)
+    }
+}
 
 pub struct ActionPlugin;
 
@@ -242,6 +360,20 @@ impl Plugin for ActionPlugin {
     fn build(&self, app: &mut bevy::app::App) {
         app.add_message::<crate::actions::execute::ChoiceRequested>()
             .add_message::<crate::board::placement::CardPlayed>()
+            .add_message::<TrapRevealed>()
+            .add_message::<CreatureDamaged>()
+            .add_message::<CreatureDestroyed>()
+            .add_message::<CardMoved>()
+            .add_message::<CreatureAttacked>()
+            .add_message::<CardsDrawn>()
+            .add_message::<DeckedOut>()
+            .add_message::<PassPriority>()
+            .init_resource::<Ruleset>()
+            .init_resource::<EffectStack>()
+            .init_resource::<Priority>()
+            .add_observer(register_hooks)
+            .add_observer(push_ability)
+            .add_observer(pop_ability)
             .add_observer(apply_deal_damage)
             .add_observer(apply_heal)
             .add_observer(apply_draw_cards)
@@ -253,11 +385,45 @@ impl Plugin for ActionPlugin {
             .add_observer(apply_discard_cards)
             .add_observer(apply_mill)
             .add_observer(apply_return_to_hand)
-            .add_systems(bevy::app::Update, drive_abilities)
+            .add_systems(
+                bevy::app::Update,
+                (pass_priority, drive_abilities, sync_acting_player).chain(),
+            )
             .add_systems(bevy::app::Update, on_card_played)
             .add_systems(
-                OnEnter(crate::turn_controller::TurnState::EndTurn),
-                on_turn_end,
+                bevy::app::Update,
+                (
+                    on_trap_revealed,
+                    on_creature_destroyed,
+                    on_creature_damaged,
+                    on_card_moved,
+                    on_creature_attacked,
+                    on_cards_drawn,
+                    on_decked_out,
+                ),
+            )
+            .add_systems(
+                OnEnter(TurnState::EndTurn),
+                (on_turn_end, fire_delayed_abilities(Phase::End))
+                    .run_if(not(resource_exists::<TurnEnding>)),
+            )
+            .add_systems(OnEnter(TurnPhase::End), expire_end_of_turn_modifiers)
+            .add_systems(
+                OnEnter(TurnPhase::Start),
+                (
+                    reset_used_hooks,
+                    tick_stat_modifiers,
+                    on_turn_start,
+                    (
+                        tick_delayed_abilities,
+                        fire_delayed_abilities(Phase::Start),
+                    )
+                        .chain(),
+                ),
+            )
+            .add_systems(
+                OnEnter(TurnPhase::Main),
+                fire_delayed_abilities(Phase::Main),
             );
     }
 }
@@ -6,39 +6,63 @@ use crate::{
     board::{
         effect::Effect,
         tile::{Occupant, Position},
-        BoardRes, EffectRequested,
+        BoardRes, EffectRequested, PlayerBase,
     },
     card::{
-        creature::{BaseAttack, BaseDefense, BaseMovementPoints},
-        CreatureCard, CurrentAttack, CurrentDefense, CurrentMovementPoints, InDeck, InGraveyard,
-        InHand, OnBoard,
+        modifiers::ModifiedCreature, CreatureCard, CurrentDefense, InDeck, InGraveyard, InHand,
+        OnBoard,
     },
     components::{Health, Owner},
-    events::CardsDrawn,
-    player::{Deck, Hand, Player, PlayerResources},
+    events::{CardMoved, CardsDrawn, CreatureDamaged, CreatureDestroyed, DeckedOut},
+    player::{Deck, Hand, PlayerResources},
+    rules::{DeckOut, Ruleset},
 };
 
 use super::{
+    hooks::TriggerHookedExt,
     AddGold, ApplyEffect, DealDamage, DestroyCreature, DiscardCards, DrawCards, HealCreature,
     Mill, ModifyStats, MoveCreature, ReturnToHand,
 };
 
+type CreatureOnBoard = (With<CreatureCard>, With<OnBoard>);
+
+/// Damages a base or a creature on the board.
 pub fn apply_deal_damage(
     trigger: On<DealDamage>,
-    mut creatures: Query<(&mut CurrentDefense, &Health, &Owner, Entity), With<CreatureCard>>,
+    mut creatures: Query<(&mut CurrentDefense, Entity), CreatureOnBoard>,
+    mut bases: Query<&mut Health, (With<PlayerBase>, Without<CreatureCard>)>,
     mut commands: Commands,
+    mut damaged: MessageWriter<CreatureDamaged>,
 ) {
     let event = trigger.event();
     let target = trigger.event_target();
-    let Ok((mut defense, _health, owner, entity)) = creatures.get_mut(target) else {
+
+    // Bases track their remaining hit points directly in `Health`.
+    if let Ok(mut health) = bases.get_mut(target) {
+        let remaining = health.value().saturating_sub(event.amount);
+        health.set_value(remaining);
         return;
-    };
+    }
 
+    let Ok((mut defense, entity)) = creatures.get_mut(target) else {
+        return;
+    };
     let damage = event.amount;
     defense.0 = defense.0.saturating_sub(damage);
+    damaged.write(CreatureDamaged {
+        creature: entity,
+        source: event.source,
+        amount: damage,
+    });
 
+    // Lethal damage destroys the creature like any other destruction, so
+    // hooks may still prevent it.
     if defense.0 == 0 {
-        destroy_creature(entity, owner.0, &mut commands);
+        let destroy = DestroyCreature::new(entity);
+        commands.trigger_hooked(match event.source {
+            Some(source) => destroy.with_source(source),
+            None => destroy,
+        });
     }
 }
 
@@ -66,25 +90,42 @@ pub fn apply_add_gold(trigger: On<AddGold>, mut players: Query<&mut PlayerResour
     resources.gold = resources.gold.saturating_add(event.amount);
 }
 
+/// Draws cards from the top of the target player's deck. Every card missing
+/// from an empty deck is handled by the ruleset's deck-out rule.
 pub fn apply_draw_cards(
     trigger: On<DrawCards>,
-    mut decks: Query<&mut Deck>,
-    mut hands: Query<&mut Hand>,
+    mut players: Query<(&mut Deck, &mut Hand, &mut PlayerResources)>,
+    rules: Res<Ruleset>,
     mut commands: Commands,
     mut drawn: MessageWriter<CardsDrawn>,
+    mut decked_out: MessageWriter<DeckedOut>,
 ) {
     let event = trigger.event();
     let target = trigger.event_target();
     let amount = event.amount as usize;
 
-    let Ok(mut deck) = decks.get_mut(target) else {
-        return;
-    };
-    let Ok(mut hand) = hands.get_mut(target) else {
+    let Ok((mut deck, mut hand, mut resources)) = players.get_mut(target) else {
         return;
     };
 
     let to_draw: Vec<Entity> = deck.iter().take(amount).collect();
+    for _ in to_draw.len()..amount {
+        let fatigue = match rules.deck_out {
+            DeckOut::Lose => {
+                resources.health = 0;
+                None
+            }
+            DeckOut::Fatigue => {
+                resources.fatigue = resources.fatigue.saturating_add(1);
+                resources.health = resources.health.saturating_sub(resources.fatigue);
+                Some(resources.fatigue)
+            }
+        };
+        decked_out.write(DeckedOut {
+            player: target,
+            fatigue,
+        });
+    }
     for card_entity in to_draw {
         commands
             .entity(card_entity)
@@ -98,9 +139,12 @@ pub fn apply_draw_cards(
     hand.set_changed();
 }
 
+/// Lays the effect on the target tile for the owner of its source. Effects
+/// without an owned source are skipped, as nobody could tick them down.
 pub fn apply_apply_effect(
     trigger: On<ApplyEffect>,
     tiles: Query<&Position>,
+    owners: Query<&Owner>,
     mut effect_requests: MessageWriter<EffectRequested>,
 ) {
     let event = trigger.event();
@@ -110,49 +154,33 @@ pub fn apply_apply_effect(
         return;
     };
 
+    let Some(&Owner(owner)) = event.source.and_then(|source| owners.get(source).ok()) else {
+        warn!(
+            "Skipping effect {:?} on tile {}: its source has no owner",
+            event.effect, target
+        );
+        return;
+    };
+
     // The executor already resolved the selected tile as the event target.
     // We request the board system to place the effect on that tile.
     effect_requests.write(EffectRequested {
-        effect: Effect::new(event.effect, event.duration, Player { number: 0 }),
+        effect: Effect::new(event.effect.clone(), event.duration, owner),
         indices: vec![pos],
     });
 }
 
 pub fn apply_modify_stats(
     trigger: On<ModifyStats>,
-    mut creatures: Query<
-        (
-            &mut CurrentAttack,
-            &mut CurrentDefense,
-            &Health,
-            &BaseAttack,
-            &BaseDefense,
-            &BaseMovementPoints,
-            &mut CurrentMovementPoints,
-        ),
-        With<CreatureCard>,
-    >,
+    mut creatures: Query<ModifiedCreature, With<CreatureCard>>,
 ) {
     let event = trigger.event();
     let target = trigger.event_target();
-    let Ok((mut attack, mut defense, health, _base_attack, _base_defense, _base_speed, mut speed)) =
-        creatures.get_mut(target)
-    else {
+    let Ok(mut creature) = creatures.get_mut(target) else {
         return;
     };
 
-    // Apply the modifier. MaxHealth changes are applied to a local copy only;
-    // Health is treated as the immutable max-HP source for this fix pass.
-    let mut max_health = health.value();
-    event.stat_modifier.apply(
-        &mut attack.0,
-        &mut defense.0,
-        &mut max_health,
-        &mut speed.0,
-    );
-
-    // Allow temporary buffs to exceed base stats, but keep current HP at or below max HP.
-    defense.0 = defense.0.min(health.value());
+    creature.modify(event.stat_modifier, event.duration, event.source);
 }
 
 pub fn apply_move_creature(
@@ -162,6 +190,7 @@ pub fn apply_move_creature(
     occupied: Query<&Occupant>,
     tiles: Query<&Position>,
     mut commands: Commands,
+    mut moved: MessageWriter<CardMoved>,
 ) {
     let event = trigger.event();
     let target = trigger.event_target();
@@ -197,40 +226,47 @@ pub fn apply_move_creature(
     }
 
     commands.entity(target).insert(OnBoard { position: new_tile });
+    moved.write(CardMoved {
+        card: target,
+        from: current_pos,
+        to: next_pos,
+    });
 }
 
+/// Sends a creature to its owner's graveyard. A creature already there is
+/// left alone, so lethal hits queued together destroy it only once.
 pub fn apply_destroy_creature(
     trigger: On<DestroyCreature>,
     mut commands: Commands,
     owners: Query<&Owner>,
+    buried: Query<(), With<InGraveyard>>,
+    mut destroyed: MessageWriter<CreatureDestroyed>,
 ) {
     let target = trigger.event_target();
+    if buried.contains(target) {
+        return;
+    }
     let owner = owners.get(target).map(|o| o.0).unwrap_or(Entity::PLACEHOLDER);
     destroy_creature(target, owner, &mut commands);
+    destroyed.write(CreatureDestroyed {
+        creature: target,
+        killer: trigger.event().source,
+    });
 }
 
-pub fn apply_discard_cards(
-    trigger: On<DiscardCards>,
-    mut commands: Commands,
-    cards: Query<(Entity, &InHand, &Owner)>,
-) {
+/// Discards the cards drawn last: the end of the hand goes first.
+pub fn apply_discard_cards(trigger: On<DiscardCards>, mut commands: Commands, hands: Query<&Hand>) {
     let event = trigger.event();
     let target = trigger.event_target();
-    let amount = event.amount as usize;
+    let Ok(hand) = hands.get(target) else {
+        return;
+    };
 
-    let mut discarded = 0;
-    for (card_entity, in_hand, owner) in &cards {
-        if discarded >= amount {
-            break;
-        }
-        if owner.0 != target || in_hand.parent != target {
-            continue;
-        }
+    for card_entity in hand.iter().rev().take(event.amount as usize) {
         commands
             .entity(card_entity)
             .remove::<InHand>()
             .insert(InGraveyard { owner: target });
-        discarded += 1;
     }
 }
 
@@ -282,3 +318,154 @@ fn destroy_creature(entity: Entity, owner: Entity, commands: &mut Commands) {
         .remove::<InDeck>()
         .insert(InGraveyard { owner });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::effect::EffectType, player::PlayerBundle};
+
+    fn draw_app(deck_out: DeckOut) -> App {
+        let mut app = App::new();
+        app.add_message::<CardsDrawn>()
+            .add_message::<DeckedOut>()
+            .insert_resource(Ruleset {
+                deck_out,
+                ..Default::default()
+            })
+            .add_observer(apply_draw_cards);
+        app
+    }
+
+    fn decked_out(app: &App) -> Vec<Option<u16>> {
+        let messages = app.world().resource::<Messages<DeckedOut>>();
+        messages
+            .iter_current_update_messages()
+            .map(|event| event.fatigue)
+            .collect()
+    }
+
+    #[test]
+    fn drawing_from_an_empty_deck_deals_escalating_fatigue() {
+        let mut app = draw_app(DeckOut::Fatigue);
+        let player = app.world_mut().spawn(PlayerBundle::default()).id();
+        app.world_mut().spawn(InDeck { parent: player });
+
+        app.world_mut().trigger(DrawCards::new(3, player));
+        app.world_mut().flush();
+
+        assert_eq!(decked_out(&app), vec![Some(1), Some(2)]);
+        let resources = app.world().get::<PlayerResources>(player).unwrap();
+        assert_eq!(resources.health, PlayerResources::default().health - 3);
+        assert_eq!(app.world().get::<Hand>(player).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn drawing_from_an_empty_deck_can_lose_the_game() {
+        let mut app = draw_app(DeckOut::Lose);
+        let player = app.world_mut().spawn(PlayerBundle::default()).id();
+
+        app.world_mut().trigger(DrawCards::new(1, player));
+
+        assert_eq!(decked_out(&app), vec![None]);
+        assert_eq!(app.world().get::<PlayerResources>(player).unwrap().health, 0);
+    }
+
+    #[test]
+    fn discarding_takes_the_cards_drawn_last() {
+        let mut app = App::new();
+        app.add_observer(apply_discard_cards);
+        let player = app.world_mut().spawn(PlayerBundle::default()).id();
+        let cards: Vec<Entity> = (0..3)
+            .map(|_| app.world_mut().spawn(InHand { parent: player }).id())
+            .collect();
+
+        app.world_mut().trigger(DiscardCards::new(2, player));
+        app.world_mut().flush();
+
+        let hand = app.world().get::<Hand>(player).unwrap();
+        assert_eq!(hand.iter().collect::<Vec<_>>(), vec![cards[0]]);
+        for card in &cards[1..] {
+            assert!(app.world().get::<InGraveyard>(*card).is_some());
+        }
+    }
+
+    #[test]
+    fn creatures_are_destroyed_once_however_often_they_are_hit() {
+        let mut app = App::new();
+        app.add_message::<CreatureDamaged>()
+            .add_message::<CreatureDestroyed>()
+            .add_observer(apply_deal_damage)
+            .add_observer(apply_destroy_creature);
+        let player = app.world_mut().spawn(PlayerBundle::default()).id();
+        let tile = app.world_mut().spawn_empty().id();
+        let creature = app
+            .world_mut()
+            .spawn((
+                CreatureCard,
+                CurrentDefense(2),
+                Owner(player),
+                OnBoard { position: tile },
+            ))
+            .id();
+
+        for _ in 0..3 {
+            app.world_mut().trigger(DealDamage::new(3, creature));
+        }
+        app.world_mut().flush();
+
+        let destroyed = app.world().resource::<Messages<CreatureDestroyed>>();
+        assert_eq!(destroyed.iter_current_update_messages().count(), 1);
+        assert!(app.world().get::<InGraveyard>(creature).is_some());
+    }
+
+    #[test]
+    fn effects_are_only_laid_for_the_owner_of_their_source() {
+        let mut app = App::new();
+        app.add_message::<EffectRequested>()
+            .add_observer(apply_apply_effect);
+        let world = app.world_mut();
+        let player = world.spawn_empty().id();
+        let card = world.spawn(Owner(player)).id();
+        let tile = world.spawn(Position(U16Vec2::new(2, 3))).id();
+        let frozen = EffectType::new("Frozen");
+
+        world.trigger(ApplyEffect::new(frozen.clone(), 2, tile));
+        world.trigger(ApplyEffect::new(frozen.clone(), 2, tile).with_source(tile));
+        world.trigger(ApplyEffect::new(frozen.clone(), 2, tile).with_source(card));
+        world.flush();
+
+        let requests = app.world().resource::<Messages<EffectRequested>>();
+        let owners: Vec<Entity> = requests
+            .iter_current_update_messages()
+            .map(|request| request.effect.owner.0)
+            .collect();
+        assert_eq!(owners, vec![player]);
+    }
+
+    #[test]
+    fn creatures_kept_alive_at_zero_defense_still_take_damage() {
+        let mut app = App::new();
+        app.add_message::<CreatureDamaged>()
+            .add_message::<CreatureDestroyed>()
+            .add_observer(apply_deal_damage)
+            .add_observer(apply_destroy_creature);
+        let player = app.world_mut().spawn(PlayerBundle::default()).id();
+        let tile = app.world_mut().spawn_empty().id();
+        let creature = app
+            .world_mut()
+            .spawn((
+                CreatureCard,
+                CurrentDefense(0),
+                Owner(player),
+                OnBoard { position: tile },
+            ))
+            .id();
+
+        app.world_mut().trigger(DealDamage::new(1, creature));
+        app.world_mut().flush();
+
+        let damaged = app.world().resource::<Messages<CreatureDamaged>>();
+        assert_eq!(damaged.iter_current_update_messages().count(), 1);
+        assert!(app.world().get::<InGraveyard>(creature).is_some());
+    }
+}
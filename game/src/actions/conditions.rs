@@ -67,7 +67,7 @@ impl Condition {
                 let Ok(tile) = params.tiles.get(tile_entity) else {
                     return false;
                 };
-                tile.children.iter().any(|&child| {
+                tile.children.into_iter().flatten().any(|&child| {
                     params
                         .effects
                         .get(child)
@@ -1,13 +1,17 @@
 //! Ability executor: drives `AbilityData` components through their effect stack,
 //! emitting `EntityEvent`s and pausing for player choices / manual targeting.
 
-use bevy::{math::I16Vec2, prelude::*};
+use bevy::{ecs::system::SystemParam, math::I16Vec2, prelude::*};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     GameRng,
     actions::{
         AbilityData, Action,
         conditions::Condition,
+        hooks::TriggerHookedExt,
+        stack::ResolvingAbility,
+        timing::ActionTiming,
         targeting::{AnyTargetSelector, FinalizeEffect},
         value_source::ValueEvalParams,
     },
@@ -15,11 +19,15 @@ use crate::{
         placement::CardPlayed,
         tile::Occupant,
     },
-    card::{CreatureCard, OnBoard},
+    card::{CreatureCard, InHand, OnBoard},
     components::Owner,
     def::{effect::EffectDef, trigger::TriggerDef},
     error::GameError,
-    events::TurnEnd,
+    events::{
+        CardMoved, CardsDrawn, CreatureAttacked, CreatureDamaged, CreatureDestroyed, DeckedOut,
+        TrapRevealed, TurnEnd,
+    },
+    phases::Phase,
     player::TurnPlayer,
     turn_controller::TurnState,
 };
@@ -27,8 +35,8 @@ use crate::{
 use super::{
     targeting::filters::FilterParams,
     value_source::{StatModifier, ValueSource},
-    AddGold, ApplyEffect, DealDamage, DestroyCreature, DiscardCards, DrawCards, HealCreature,
-    Mill, ModifyStats, MoveCreature, ReturnToHand,
+    AddGold, ApplyEffect, DealDamage, DestroyCreature, DiscardCards, DrawCards, EndTurn,
+    HealCreature, Mill, ModifyStats, MoveCreature, ReturnToHand,
 };
 
 /// Remaining effect stack for a running ability.
@@ -69,43 +77,82 @@ pub struct AwaitingChoice {
     pub kind: AwaitingChoiceKind,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AwaitingChoiceKind {
     Options(Vec<String>),
     Entities,
 }
 
-/// Start executing an ability by spawning a cursor on its ability entity.
-/// If the ability's condition is not met, the ability is skipped.
+/// One run of an ability: its cursor while on the stack, or its delay and
+/// context while queued. Every trigger spawns its own, as a child of the
+/// ability, so runs of the same ability never overwrite each other.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Activation {
+    pub ability: Entity,
+    pub caster: Entity,
+}
+
+/// Start executing an ability by spawning an activation with a cursor.
+/// `target` seeds the ability's current target, e.g. the creature that
+/// sprang a trap. If the ability's condition is not met, the ability is
+/// skipped and `false` returned. Abilities with delayed timing are queued
+/// instead and started by [`fire_delayed_abilities`] once their delay runs
+/// out; abilities without effects have nothing to run.
 pub fn start_ability(
     commands: &mut Commands,
     ability_entity: Entity,
     ability: &AbilityData,
     caster: Entity,
+    target: Option<Entity>,
     params: &mut ValueEvalParams,
-) {
+) -> bool {
     let condition = match Condition::try_from(&ability.0.condition) {
         Ok(c) => c,
         Err(e) => {
             warn!("Failed to evaluate ability condition: {}", e);
-            return;
+            return false;
         }
     };
 
+    params.current_target = target;
     if !condition.eval(params, caster) {
-        return;
+        return false;
+    }
+    if ability.0.effects.is_empty() {
+        return true;
     }
 
-    commands.entity(ability_entity).insert(AbilityCursor {
-        stack: ability.0.effects.clone(),
-        context: AbilityContext::default(),
-    });
+    let context = AbilityContext {
+        current_target: target,
+        ..Default::default()
+    };
+    let activation = (
+        Activation {
+            ability: ability_entity,
+            caster,
+        },
+        ChildOf(ability_entity),
+    );
+    if let ActionTiming::Delayed { .. } = ability.0.timing {
+        commands.spawn((activation, ability.0.timing, context));
+        return true;
+    }
+
+    commands.spawn((
+        activation,
+        AbilityCursor {
+            stack: ability.0.effects.clone(),
+            context,
+        },
+    ));
+    true
 }
 
-/// Driver system: processes ability cursors each frame.
+/// Driver system: advances the ability at the top of the effect stack by one
+/// effect per frame, once every player has passed priority on it.
 pub fn drive_abilities(
     mut commands: Commands,
-    mut cursors: Query<(Entity, &AbilityData, &mut AbilityCursor, &Action), Without<AwaitingChoice>>,
+    mut resolving: ResolvingAbility,
     filter_params: FilterParams,
     mut rng: ResMut<GameRng>,
     occupied: Query<&Occupant>,
@@ -113,46 +160,47 @@ pub fn drive_abilities(
     mut next_state: ResMut<NextState<TurnState>>,
     mut choice_requests: MessageWriter<ChoiceRequested>,
 ) {
+    let Some((cursor_entity, mut cursor, activation)) = resolving.get_mut() else {
+        return;
+    };
+    if cursor.stack.is_empty() {
+        commands.entity(cursor_entity).despawn();
+        return;
+    }
+
     let mut value_params = filter_params.as_value_params(&mut *rng);
+    value_params.current_target = cursor.context.current_target;
 
-    for (ability_entity, _ability, mut cursor, action) in &mut cursors {
-        if cursor.stack.is_empty() {
-            commands.entity(ability_entity).remove::<AbilityCursor>();
-            continue;
-        }
-
-        let effect = cursor.stack.remove(0);
-        match execute_effect(
-            &mut commands,
-            ability_entity,
-            &mut cursor,
-            action.caster,
-            &effect,
-            &mut value_params,
-            &occupied,
-            &creatures,
-        ) {
-            Ok(EffectResult::Done) => {}
-            Ok(EffectResult::NeedsChoice(request)) => {
-                let kind = match &request {
-                    ChoiceRequested::Options { labels, .. } => {
-                        AwaitingChoiceKind::Options(labels.clone())
-                    }
-                    ChoiceRequested::Entities { .. } => AwaitingChoiceKind::Entities,
-                };
-                commands.entity(ability_entity).insert(AwaitingChoice {
-                    cursor: ability_entity,
-                    kind,
-                });
-                choice_requests.write(request);
-                // Re-insert the effect we removed so it is re-tried after the choice.
-                cursor.stack.insert(0, effect);
-                next_state.set(TurnState::AwaitingInputs);
-                break;
-            }
-            Err(e) => {
-                warn!("Ability execution error: {}", e);
-            }
+    let effect = cursor.stack.remove(0);
+    match execute_effect(
+        &mut commands,
+        cursor_entity,
+        &mut cursor,
+        activation.caster,
+        &effect,
+        &mut value_params,
+        &occupied,
+        &creatures,
+    ) {
+        Ok(EffectResult::Done) => {}
+        Ok(EffectResult::NeedsChoice(request)) => {
+            let kind = match &request {
+                ChoiceRequested::Options { labels, .. } => {
+                    AwaitingChoiceKind::Options(labels.clone())
+                }
+                ChoiceRequested::Entities { .. } => AwaitingChoiceKind::Entities,
+            };
+            commands.entity(cursor_entity).insert(AwaitingChoice {
+                cursor: cursor_entity,
+                kind,
+            });
+            choice_requests.write(request);
+            // Re-insert the effect we removed so it is re-tried after the choice.
+            cursor.stack.insert(0, effect);
+            next_state.set(TurnState::AwaitingInputs);
+        }
+        Err(e) => {
+            warn!("Ability execution error: {}", e);
         }
     }
 }
@@ -329,55 +377,49 @@ fn emit_effect_events(
         EffectDef::DealDamage { amount, .. } => {
             let amount = eval_value(amount, params, caster)?;
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| DealDamage::new(amount, e));
+                commands.trigger_hooked(DealDamage::new(amount, target).with_source(caster));
             }
         }
         EffectDef::Heal { amount, .. } => {
             let amount = eval_value(amount, params, caster)?;
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| HealCreature::new(amount, e));
+                commands.trigger_hooked(HealCreature::new(amount, target));
             }
         }
         EffectDef::DrawCards { amount, .. } => {
             let amount = eval_value(amount, params, caster)?;
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| DrawCards::new(amount, e));
+                commands.trigger_hooked(DrawCards::new(amount, target));
             }
         }
         EffectDef::AddGold { amount, .. } => {
             let amount = eval_value(amount, params, caster)?;
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| AddGold::new(amount, e));
+                commands.trigger_hooked(AddGold::new(amount, target));
             }
         }
         EffectDef::ApplyEffect { effect, duration, .. } => {
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| ApplyEffect::new(*effect, *duration, e));
+                commands.trigger_hooked(
+                    ApplyEffect::new(effect.clone(), *duration, target).with_source(caster),
+                );
             }
         }
         EffectDef::DestroyCreature { .. } => {
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| DestroyCreature::new(e));
+                commands.trigger_hooked(DestroyCreature::new(target).with_source(caster));
             }
         }
-        EffectDef::ModifyStats { modifier, .. } => {
+        EffectDef::ModifyStats {
+            modifier, duration, ..
+        } => {
             let modifier: StatModifier = modifier.into();
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| ModifyStats::new(e, modifier));
+                commands.trigger_hooked(
+                    ModifyStats::new(target, modifier)
+                        .with_duration(*duration)
+                        .with_source(caster),
+                );
             }
         }
         EffectDef::MoveCreature {
@@ -390,32 +432,24 @@ fn emit_effect_events(
                 let Some(creature) = find_creature_on_tile(tile, occupied, creatures) else {
                     continue;
                 };
-                commands
-                    .entity(creature)
-                    .trigger(|e| MoveCreature::new(direction, *absolute, e));
+                commands.trigger_hooked(MoveCreature::new(direction, *absolute, creature));
             }
         }
         EffectDef::DiscardCards { amount, .. } => {
             let amount = eval_value(amount, params, caster)?;
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| DiscardCards::new(amount, e));
+                commands.trigger_hooked(DiscardCards::new(amount, target));
             }
         }
         EffectDef::Mill { amount, .. } => {
             let amount = eval_value(amount, params, caster)?;
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| Mill::new(amount, e));
+                commands.trigger_hooked(Mill::new(amount, target));
             }
         }
         EffectDef::ReturnToHand { .. } => {
             for &target in targets {
-                commands
-                    .entity(target)
-                    .trigger(|e| ReturnToHand::new(e));
+                commands.trigger_hooked(ReturnToHand::new(target));
             }
         }
         EffectDef::If { .. } | EffectDef::Choose { .. } => unreachable!(),
@@ -443,82 +477,197 @@ fn find_creature_on_tile(
     Some(creature)
 }
 
-/// System that starts OnPlay abilities when a card is played.
-pub fn on_card_played(
-    mut plays: MessageReader<CardPlayed>,
-    abilities: Query<(Entity, &AbilityData, &Action)>,
-    mut commands: Commands,
-    filter_params: FilterParams,
-    mut rng: ResMut<GameRng>,
-) {
-    let mut value_params = filter_params.as_value_params(&mut *rng);
-    for play in plays.read() {
-        for (ability_entity, ability, action) in &abilities {
-            if action.caster != play.card {
-                continue;
-            }
-            if ability.0.trigger != TriggerDef::OnPlay {
+/// Starts the abilities a card has for a trigger as the trigger fires.
+#[derive(SystemParam)]
+pub struct AbilityStarter<'w, 's> {
+    commands: Commands<'w, 's>,
+    abilities: Query<'w, 's, (Entity, &'static AbilityData, &'static Action)>,
+    filter_params: FilterParams<'w, 's>,
+    rng: ResMut<'w, GameRng>,
+}
+
+impl AbilityStarter<'_, '_> {
+    /// Starts every `trigger` ability of `card` with `target` as its current
+    /// target.
+    pub fn start(&mut self, card: Entity, trigger: TriggerDef, target: Option<Entity>) {
+        let mut value_params = self.filter_params.as_value_params(&mut self.rng);
+        for (ability_entity, ability, action) in &self.abilities {
+            if action.caster != card || ability.0.trigger != trigger {
                 continue;
             }
             start_ability(
-                &mut commands,
+                &mut self.commands,
                 ability_entity,
                 ability,
                 action.caster,
+                target,
                 &mut value_params,
             );
         }
     }
 }
 
-/// Stub for OnReveal trap triggers. Trap reveal events are not yet wired; log a warning.
-pub fn on_reveal_stub(
-    abilities: Query<(Entity, &AbilityData, &Action)>,
+/// System that starts OnPlay abilities when a card is played.
+pub fn on_card_played(mut plays: MessageReader<CardPlayed>, mut starter: AbilityStarter) {
+    for play in plays.read() {
+        starter.start(play.card, TriggerDef::OnPlay, None);
+    }
+}
+
+/// System that starts OnReveal abilities when a trap is turned over. The
+/// creature that sprang the trap becomes the abilities' current target.
+pub fn on_trap_revealed(mut reveals: MessageReader<TrapRevealed>, mut starter: AbilityStarter) {
+    for reveal in reveals.read() {
+        starter.start(reveal.trap, TriggerDef::OnReveal, reveal.intruder);
+    }
+}
+
+/// System that starts OnDeath abilities of destroyed creatures, targeting
+/// whatever destroyed them.
+pub fn on_creature_destroyed(
+    mut destroyed: MessageReader<CreatureDestroyed>,
+    mut starter: AbilityStarter,
 ) {
-    for (_entity, ability, _action) in &abilities {
-        if ability.0.trigger == TriggerDef::OnReveal {
-            warn!("OnReveal ability found but trap reveal flow is not yet implemented");
+    for event in destroyed.read() {
+        starter.start(event.creature, TriggerDef::OnDeath, event.killer);
+    }
+}
+
+/// System that starts OnDamaged abilities, targeting the damage source.
+pub fn on_creature_damaged(
+    mut damaged: MessageReader<CreatureDamaged>,
+    mut starter: AbilityStarter,
+) {
+    for event in damaged.read() {
+        starter.start(event.creature, TriggerDef::OnDamaged, event.source);
+    }
+}
+
+/// System that starts OnMove abilities, targeting the tile moved to.
+pub fn on_card_moved(
+    mut moved: MessageReader<CardMoved>,
+    on_board: Query<&OnBoard>,
+    mut starter: AbilityStarter,
+) {
+    for event in moved.read() {
+        let tile = on_board.get(event.card).ok().map(|on_board| on_board.position);
+        starter.start(event.card, TriggerDef::OnMove, tile);
+    }
+}
+
+/// System that starts OnAttack abilities, targeting the attacked entity.
+pub fn on_creature_attacked(
+    mut attacks: MessageReader<CreatureAttacked>,
+    mut starter: AbilityStarter,
+) {
+    for attack in attacks.read() {
+        starter.start(attack.attacker, TriggerDef::OnAttack, Some(attack.target));
+    }
+}
+
+/// System that starts OnDraw abilities, targeting the player who drew.
+pub fn on_cards_drawn(
+    mut drawn: MessageReader<CardsDrawn>,
+    hands: Query<&InHand>,
+    mut starter: AbilityStarter,
+) {
+    for event in drawn.read() {
+        let player = hands.get(event.card).ok().map(|in_hand| in_hand.parent);
+        starter.start(event.card, TriggerDef::OnDraw, player);
+    }
+}
+
+/// System that starts OnDeckOut abilities of the creatures of a player who
+/// had to draw from an empty deck, targeting that player.
+pub fn on_decked_out(
+    mut decked_out: MessageReader<DeckedOut>,
+    on_board: Query<(Entity, &Owner), CreatureOnBoard>,
+    mut starter: AbilityStarter,
+) {
+    for event in decked_out.read() {
+        for (creature, owner) in &on_board {
+            if owner.0 == event.player {
+                starter.start(creature, TriggerDef::OnDeckOut, Some(event.player));
+            }
+        }
+    }
+}
+
+type CreatureOnBoard = (With<CreatureCard>, With<OnBoard>);
+
+/// System that triggers OnTurnStart abilities of the turn player's
+/// creatures, targeting the turn player.
+pub fn on_turn_start(
+    turn_player: Query<Entity, With<TurnPlayer>>,
+    on_board: Query<(Entity, &Owner), CreatureOnBoard>,
+    mut starter: AbilityStarter,
+) {
+    let Ok(player) = turn_player.single() else {
+        return;
+    };
+    for (creature, owner) in &on_board {
+        if owner.0 == player {
+            starter.start(creature, TriggerDef::OnTurnStart, Some(player));
         }
     }
 }
 
-/// System that triggers OnTurnEnd abilities at the end of the turn.
+/// System that triggers OnTurnEnd abilities and `TurnEnded` hooks at the end
+/// of the turn.
 pub fn on_turn_end(
     mut commands: Commands,
     turn_player: Query<Entity, With<TurnPlayer>>,
-    on_board: Query<(Entity, &Owner), With<CreatureCard>>,
-    abilities: Query<(Entity, &AbilityData, &Action)>,
+    on_board: Query<(Entity, &Owner), CreatureOnBoard>,
     mut turn_end_writer: MessageWriter<TurnEnd>,
-    filter_params: FilterParams,
-    mut rng: ResMut<GameRng>,
+    mut starter: AbilityStarter,
 ) {
     let Ok(player) = turn_player.single() else {
         return;
     };
-
-    let mut value_params = filter_params.as_value_params(&mut *rng);
-    for (ability_entity, ability, action) in &abilities {
-        if ability.0.trigger != TriggerDef::OnTurnEnd {
-            continue;
+    for (creature, owner) in &on_board {
+        if owner.0 == player {
+            starter.start(creature, TriggerDef::OnTurnEnd, None);
         }
-        let Ok((_, owner)) = on_board.get(action.caster) else {
-            continue;
-        };
-        if owner.0 != player {
-            continue;
-        }
-        start_ability(
-            &mut commands,
-            ability_entity,
-            ability,
-            action.caster,
-            &mut value_params,
-        );
     }
 
+    commands.trigger_hooked(EndTurn(player));
     turn_end_writer.write(TurnEnd);
 }
 
+/// Counts every queued activation's delay down by one turn.
+pub fn tick_delayed_abilities(mut queued: Query<&mut ActionTiming, With<Activation>>) {
+    for mut timing in &mut queued {
+        *timing -= 1;
+    }
+}
+
+type QueuedActivations<'w, 's> =
+    Query<'w, 's, (Entity, &'static Activation, &'static ActionTiming, &'static AbilityContext)>;
+
+/// A system starting the queued activations that are due in `phase`, i.e.
+/// whose delay has counted down to zero turns.
+pub fn fire_delayed_abilities(
+    phase: Phase,
+) -> impl Fn(Commands, QueuedActivations, Query<&AbilityData>) {
+    move |mut commands, queued, abilities| {
+        for (activation, queued, timing, context) in &queued {
+            if *timing != (ActionTiming::Delayed { phase, turns: 0 }) {
+                continue;
+            }
+            let Ok(ability) = abilities.get(queued.ability) else {
+                continue;
+            };
+            commands
+                .entity(activation)
+                .remove::<(ActionTiming, AbilityContext)>()
+                .insert(AbilityCursor {
+                    stack: ability.0.effects.clone(),
+                    context: context.clone(),
+                });
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -562,6 +711,7 @@ mod tests {
                 then: then_branch.clone(),
                 otherwise: otherwise_branch.clone(),
             }],
+            replacement: None,
         };
         let cursor = AbilityCursor {
             stack: ability.effects.clone(),
@@ -584,7 +734,7 @@ mod tests {
         use crate::{
             GameRng,
             actions::ActionPlugin,
-            card::{CreatureCard, CurrentDefense},
+            card::{CreatureCard, CurrentDefense, OnBoard},
             components::{Health, Owner},
             turn_controller::TurnControllerPlugin,
         };
@@ -596,10 +746,12 @@ mod tests {
             .init_resource::<GameRng>()
             .insert_resource(ButtonInput::<KeyCode>::default());
 
+        let tile = app.world_mut().spawn_empty().id();
         let creature = app
             .world_mut()
             .spawn((
                 CreatureCard,
+                OnBoard { position: tile },
                 CurrentDefense(5),
                 Health(5),
                 Owner(Entity::PLACEHOLDER),
@@ -729,4 +881,170 @@ mod tests {
         );
         app.update();
     }
+
+    #[test]
+    fn delayed_ability_fires_once_its_turns_ran_out() {
+        use crate::{
+            actions::ActionPlugin,
+            events::GameMessagesPlugin,
+            player::{Player, PlayerBundle, PlayerResources},
+            turn_controller::TurnControllerPlugin,
+        };
+        use bevy::ecs::system::RunSystemOnce;
+        use bevy::input::{ButtonInput, keyboard::KeyCode};
+        use bevy::state::app::StatesPlugin;
+
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let player = app
+            .world_mut()
+            .spawn((Player { number: 0 }, PlayerBundle::default(), TurnPlayer))
+            .id();
+        app.world_mut()
+            .spawn((Player { number: 1 }, PlayerBundle::default()));
+        let caster = app.world_mut().spawn(Owner(player)).id();
+        let ability = AbilityData(AbilityDef {
+            trigger: TriggerDef::OnPlay,
+            condition: ConditionDef::Always,
+            speed: Default::default(),
+            timing: ActionTiming::Delayed {
+                phase: Phase::Start,
+                turns: 1,
+            },
+            effects: vec![EffectDef::AddGold {
+                player: SelectorDef {
+                    kind: SelectorKindDef::Player,
+                    cardinality: CardinalityDef::Single,
+                    selection: SelectionDef::Owner,
+                    filters: vec![],
+                },
+                amount: ValueDef::Constant(1),
+            }],
+            replacement: None,
+        });
+        let ability_entity = app
+            .world_mut()
+            .spawn((ability.clone(), Action { caster }))
+            .id();
+        app.update();
+
+        app.world_mut()
+            .run_system_once(
+                move |mut commands: Commands,
+                      filter_params: FilterParams,
+                      mut rng: ResMut<GameRng>| {
+                    let mut value_params = filter_params.as_value_params(&mut rng);
+                    // Triggered twice before the delay runs out, it fires twice.
+                    for _ in 0..2 {
+                        start_ability(
+                            &mut commands,
+                            ability_entity,
+                            &ability,
+                            caster,
+                            None,
+                            &mut value_params,
+                        );
+                    }
+                },
+            )
+            .unwrap();
+        for _ in 0..3 {
+            app.update();
+        }
+        let gold = |app: &App| app.world().get::<PlayerResources>(player).unwrap().gold;
+        assert_eq!(gold(&app), 10);
+
+        app.world_mut()
+            .resource_mut::<NextState<TurnState>>()
+            .set(TurnState::EndTurn);
+        for _ in 0..6 {
+            app.update();
+        }
+        assert_eq!(gold(&app), 12);
+        let world = app.world_mut();
+        assert!(world.query::<&Activation>().iter(world).next().is_none());
+    }
+
+    /// OnDamaged abilities target whatever dealt the damage.
+    #[test]
+    fn on_damaged_ability_strikes_back_at_the_source() {
+        use crate::{
+            actions::ActionPlugin,
+            board::tile::{Position, Tile},
+            card::{CreatureCard, CurrentAttack, CurrentDefense, CurrentMovementPoints, OnBoard},
+            components::Health,
+            events::GameMessagesPlugin,
+            turn_controller::TurnControllerPlugin,
+        };
+        use bevy::input::{ButtonInput, keyboard::KeyCode};
+        use bevy::state::app::StatesPlugin;
+
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let spawn_creature = |app: &mut App| {
+            let world = app.world_mut();
+            let tile = world
+                .spawn((Tile, Position(bevy::math::U16Vec2::new(0, 0))))
+                .id();
+            world
+                .spawn((
+                    CreatureCard,
+                    CurrentAttack(1),
+                    CurrentDefense(5),
+                    CurrentMovementPoints(1),
+                    Health(5),
+                    Owner(Entity::PLACEHOLDER),
+                    OnBoard { position: tile },
+                ))
+                .id()
+        };
+        let source = spawn_creature(&mut app);
+        let thorns = spawn_creature(&mut app);
+        app.world_mut().spawn((
+            AbilityData(AbilityDef {
+                trigger: TriggerDef::OnDamaged,
+                condition: ConditionDef::Always,
+                speed: Default::default(),
+                timing: Default::default(),
+                effects: vec![EffectDef::DealDamage {
+                    selector: SelectorDef {
+                        kind: SelectorKindDef::Creature,
+                        cardinality: CardinalityDef::Single,
+                        selection: SelectionDef::CurrentTarget,
+                        filters: vec![],
+                    },
+                    amount: ValueDef::Constant(1),
+                }],
+                replacement: None,
+            }),
+            Action { caster: thorns },
+        ));
+        app.update();
+
+        app.world_mut()
+            .trigger(DealDamage::new(2, thorns).with_source(source));
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let defense = |entity| app.world().get::<CurrentDefense>(entity).unwrap().0;
+        assert_eq!(defense(thorns), 3);
+        assert_eq!(defense(source), 4);
+    }
 }
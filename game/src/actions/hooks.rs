@@ -1,14 +1,475 @@
-use bevy::ecs::{component::Component, event::EntityEvent, observer::On, system::Commands};
+//! Hooks let abilities react to atomic events happening to their card. A
+//! `Before` hook runs just before the event and may change or cancel it, an
+//! `After` hook runs once the event's observers applied it. Events only run
+//! their hooks when triggered through [`TriggerHookedExt::trigger_hooked`].
 
-use crate::actions::Execute;
+use std::marker::PhantomData;
 
+use bevy::{ecs::event::EntityTrigger, prelude::*};
+
+use crate::{
+    GameRng,
+    actions::{
+        AbilityData, Action, AddGold, ApplyEffect, DealDamage, DestroyCreature, DiscardCards,
+        DrawCards, EndTurn, HealCreature, Mill, ModifyStats, MoveCreature, ReturnToHand,
+        execute::start_ability,
+        targeting::filters::FilterParams,
+        value_source::{ValueEvalParams, ValueSource},
+    },
+    card::{OnBoard, SetOnTile},
+    components::Owner,
+    def::{
+        trigger::{HookDef, ReplacementDef, TriggerDef},
+        value::ValueDef,
+    },
+};
+
+/// When a hook runs relative to its event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    Before,
+    After,
+}
+
+/// Subscribes the ability entity it sits on to events of type `T`.
 #[derive(Component)]
 pub struct Hook<T: HookEvent> {
-    pd: std::marker::PhantomData<T>,
+    point: HookPoint,
+    once_per_turn: bool,
+    pd: PhantomData<T>,
+}
+
+impl<T: HookEvent> Hook<T> {
+    pub fn new(point: HookPoint, once_per_turn: bool) -> Self {
+        Self {
+            point,
+            once_per_turn,
+            pd: PhantomData,
+        }
+    }
+}
+
+/// A once-per-turn hook that already fired this turn.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HookUsedThisTurn;
+
+pub trait HookEvent: EntityEvent + for<'a> Event<Trigger<'a> = EntityTrigger> + Sized {
+    const KIND: HookDef;
+
+    /// The amount `Reduce` and `Increase` replacements change, for events
+    /// that have one.
+    fn amount_mut(&mut self) -> Option<&mut u16> {
+        None
+    }
+}
+
+pub trait TriggerHookedExt {
+    /// Triggers `event` on its target with the hooks subscribed to it
+    /// running around the event's observers.
+    fn trigger_hooked<T: HookEvent>(&mut self, event: T);
 }
 
-pub trait HookEvent: EntityEvent + Sized {}
+impl TriggerHookedExt for Commands<'_, '_> {
+    fn trigger_hooked<T: HookEvent>(&mut self, event: T) {
+        self.queue(move |world: &mut World| run_hooked(world, event));
+    }
+}
+
+impl TriggerHookedExt for World {
+    fn trigger_hooked<T: HookEvent>(&mut self, event: T) {
+        run_hooked(self, event);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HookOutcome {
+    /// The event happens, with its amount changed if given.
+    Proceed(Option<u16>),
+    Prevented,
+}
+
+fn run_hooked<T: HookEvent>(world: &mut World, mut event: T) {
+    let target = event.event_target();
+    // Only cards in play listen, i.e. creatures on the board and set traps.
+    // After hooks run for the cards that were in play when the event began,
+    // so a creature still hears of its own destruction.
+    let mut hooks = world.query_filtered::<(Entity, &Action), With<Hook<T>>>();
+    let listening: Vec<Entity> = hooks
+        .iter(world)
+        .filter(|(_, action)| {
+            world.get::<OnBoard>(action.caster).is_some()
+                || world.get::<SetOnTile>(action.caster).is_some()
+        })
+        .map(|(ability, _)| ability)
+        .collect();
+    if listening.is_empty() {
+        world.trigger(event);
+        return;
+    }
+
+    let amount = event.amount_mut().map(|amount| *amount);
+    match world.run_system_cached_with(
+        resolve_hooks::<T>,
+        (target, HookPoint::Before, amount, listening.clone()),
+    ) {
+        Ok(HookOutcome::Prevented) => {
+            info!("{:?} on {} was prevented", T::KIND, target);
+            return;
+        }
+        Ok(HookOutcome::Proceed(Some(changed))) => {
+            if let Some(amount) = event.amount_mut() {
+                *amount = changed;
+            }
+        }
+        Ok(HookOutcome::Proceed(None)) => {}
+        Err(e) => warn!("Failed to run before hooks: {}", e),
+    }
+
+    world.trigger(event);
+
+    if let Err(e) = world.run_system_cached_with(
+        resolve_hooks::<T>,
+        (target, HookPoint::After, None, listening),
+    ) {
+        warn!("Failed to run after hooks: {}", e);
+    }
+}
+
+/// Starts every hook at `point` among `listening` that listens to `target`
+/// and folds their replacements into the event's amount.
+fn resolve_hooks<T: HookEvent>(
+    In((target, point, mut amount, listening)): In<(Entity, HookPoint, Option<u16>, Vec<Entity>)>,
+    hooks: Query<(Entity, &Hook<T>, &AbilityData, &Action)>,
+    used: Query<(), With<HookUsedThisTurn>>,
+    subjects: Query<(Option<&Owner>, Option<&OnBoard>, Option<&SetOnTile>)>,
+    filter_params: FilterParams,
+    mut rng: ResMut<GameRng>,
+    mut commands: Commands,
+) -> HookOutcome {
+    let mut value_params = filter_params.as_value_params(&mut rng);
+    for (ability_entity, hook, ability, action) in &hooks {
+        if hook.point != point
+            || (hook.once_per_turn && used.contains(ability_entity))
+            || !listening.contains(&ability_entity)
+        {
+            continue;
+        }
+        if subject(T::KIND, action.caster, &subjects) != Some(target) {
+            continue;
+        }
+
+        if !start_ability(
+            &mut commands,
+            ability_entity,
+            ability,
+            action.caster,
+            Some(target),
+            &mut value_params,
+        ) {
+            continue;
+        }
+        if hook.once_per_turn {
+            commands.entity(ability_entity).insert(HookUsedThisTurn);
+        }
+
+        if point == HookPoint::After {
+            continue;
+        }
+        match &ability.0.replacement {
+            None => {}
+            Some(ReplacementDef::Prevent) => return HookOutcome::Prevented,
+            Some(ReplacementDef::Reduce(by)) => {
+                let by = eval_value(by, &mut value_params, action.caster);
+                amount = amount.map(|amount| amount.saturating_sub(by));
+            }
+            Some(ReplacementDef::Increase(by)) => {
+                let by = eval_value(by, &mut value_params, action.caster);
+                amount = amount.map(|amount| amount.saturating_add(by));
+            }
+        }
+    }
+    HookOutcome::Proceed(amount)
+}
+
+fn eval_value(value: &ValueDef, params: &mut ValueEvalParams, caster: Entity) -> u16 {
+    match ValueSource::try_from(value) {
+        Ok(source) => source.eval(params, caster),
+        Err(e) => {
+            warn!("Failed to evaluate replacement value: {}", e);
+            0
+        }
+    }
+}
+
+/// The entity a hook on `caster`'s ability listens to: the card itself, its
+/// owner for events that happen to players, or its tile for tile effects.
+fn subject(
+    kind: HookDef,
+    caster: Entity,
+    subjects: &Query<(Option<&Owner>, Option<&OnBoard>, Option<&SetOnTile>)>,
+) -> Option<Entity> {
+    let (owner, on_board, set) = subjects.get(caster).ok()?;
+    match kind {
+        HookDef::Damaged
+        | HookDef::Healed
+        | HookDef::Destroyed
+        | HookDef::Moved
+        | HookDef::StatsModified
+        | HookDef::ReturnedToHand => Some(caster),
+        HookDef::CardsDrawn
+        | HookDef::GoldGained
+        | HookDef::CardsDiscarded
+        | HookDef::Milled
+        | HookDef::TurnEnded => owner.map(|owner| owner.0),
+        HookDef::EffectApplied => on_board
+            .map(|on_board| on_board.position)
+            .or(set.map(|set| set.tile)),
+    }
+}
+
+/// Subscribes hook abilities to their event as they are spawned.
+pub fn register_hooks(
+    add: On<Add, AbilityData>,
+    abilities: Query<&AbilityData>,
+    mut commands: Commands,
+) {
+    let Ok(ability) = abilities.get(add.entity) else {
+        return;
+    };
+    let (point, event, once_per_turn) = match ability.0.trigger {
+        TriggerDef::Before {
+            event,
+            once_per_turn,
+        } => (HookPoint::Before, event, once_per_turn),
+        TriggerDef::After {
+            event,
+            once_per_turn,
+        } => (HookPoint::After, event, once_per_turn),
+        _ => return,
+    };
+
+    let mut entity = commands.entity(add.entity);
+    match event {
+        HookDef::Damaged => entity.insert(Hook::<DealDamage>::new(point, once_per_turn)),
+        HookDef::Healed => entity.insert(Hook::<HealCreature>::new(point, once_per_turn)),
+        HookDef::Destroyed => entity.insert(Hook::<DestroyCreature>::new(point, once_per_turn)),
+        HookDef::Moved => entity.insert(Hook::<MoveCreature>::new(point, once_per_turn)),
+        HookDef::StatsModified => entity.insert(Hook::<ModifyStats>::new(point, once_per_turn)),
+        HookDef::ReturnedToHand => entity.insert(Hook::<ReturnToHand>::new(point, once_per_turn)),
+        HookDef::CardsDrawn => entity.insert(Hook::<DrawCards>::new(point, once_per_turn)),
+        HookDef::GoldGained => entity.insert(Hook::<AddGold>::new(point, once_per_turn)),
+        HookDef::CardsDiscarded => entity.insert(Hook::<DiscardCards>::new(point, once_per_turn)),
+        HookDef::Milled => entity.insert(Hook::<Mill>::new(point, once_per_turn)),
+        HookDef::TurnEnded => entity.insert(Hook::<EndTurn>::new(point, once_per_turn)),
+        HookDef::EffectApplied => entity.insert(Hook::<ApplyEffect>::new(point, once_per_turn)),
+    };
+}
+
+/// Lets once-per-turn hooks fire again.
+pub fn reset_used_hooks(used: Query<Entity, With<HookUsedThisTurn>>, mut commands: Commands) {
+    for entity in &used {
+        commands.entity(entity).remove::<HookUsedThisTurn>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::ActionPlugin,
+        card::{CreatureCard, CurrentDefense, InGraveyard, InHand},
+        components::Health,
+        def::{
+            condition::ConditionDef,
+            effect::EffectDef,
+            selector::{CardinalityDef, SelectionDef, SelectorDef, SelectorKindDef},
+            trigger::AbilityDef,
+        },
+        events::GameMessagesPlugin,
+        player::{Player, PlayerBundle, PlayerResources, TurnPlayer},
+        turn_controller::{TurnControllerPlugin, TurnState},
+    };
+    use bevy::state::app::StatesPlugin;
+
+    #[test]
+    fn before_hook_prevents_the_first_damage_each_turn() {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let tile = app.world_mut().spawn_empty().id();
+        let creature = app
+            .world_mut()
+            .spawn((
+                CreatureCard,
+                OnBoard { position: tile },
+                CurrentDefense(5),
+                Health(5),
+                Owner(Entity::PLACEHOLDER),
+            ))
+            .id();
+        app.world_mut().spawn((
+            AbilityData(AbilityDef {
+                trigger: TriggerDef::Before {
+                    event: HookDef::Damaged,
+                    once_per_turn: true,
+                },
+                condition: ConditionDef::Always,
+                speed: Default::default(),
+                timing: Default::default(),
+                effects: vec![],
+                replacement: Some(ReplacementDef::Prevent),
+            }),
+            Action { caster: creature },
+        ));
+        app.update();
+
+        let defense = |app: &mut App| {
+            app.world_mut()
+                .commands()
+                .trigger_hooked(DealDamage::new(3, creature));
+            app.world_mut().flush();
+            app.world().get::<CurrentDefense>(creature).unwrap().0
+        };
+        assert_eq!(defense(&mut app), 5);
+        assert_eq!(defense(&mut app), 2);
+    }
+
+    /// A hook ability on `event` that runs `effects`. `Before` hooks prevent
+    /// the event.
+    fn hook(event: HookDef, point: HookPoint, effects: Vec<EffectDef>) -> AbilityData {
+        let once_per_turn = false;
+        let (trigger, replacement) = match point {
+            HookPoint::Before => (
+                TriggerDef::Before {
+                    event,
+                    once_per_turn,
+                },
+                Some(ReplacementDef::Prevent),
+            ),
+            HookPoint::After => (
+                TriggerDef::After {
+                    event,
+                    once_per_turn,
+                },
+                None,
+            ),
+        };
+        AbilityData(AbilityDef {
+            trigger,
+            condition: ConditionDef::Always,
+            speed: Default::default(),
+            timing: Default::default(),
+            effects,
+            replacement,
+        })
+    }
+
+    #[test]
+    fn lethal_damage_runs_destroyed_hooks() {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let tile = app.world_mut().spawn_empty().id();
+        let creature = app
+            .world_mut()
+            .spawn((
+                CreatureCard,
+                OnBoard { position: tile },
+                CurrentDefense(2),
+                Health(2),
+                Owner(Entity::PLACEHOLDER),
+            ))
+            .id();
+        app.world_mut().spawn((
+            hook(HookDef::Destroyed, HookPoint::Before, vec![]),
+            Action { caster: creature },
+        ));
+        app.update();
+
+        app.world_mut()
+            .commands()
+            .trigger_hooked(DealDamage::new(3, creature));
+        app.world_mut().flush();
+        assert_eq!(app.world().get::<CurrentDefense>(creature).unwrap().0, 0);
+        assert!(app.world().get::<InGraveyard>(creature).is_none());
+    }
+
+    /// The gold of a player after their turn ends with a card placed by
+    /// `place`, if any, that gains them one gold when it does.
+    fn gold_after_turn_end(place: impl FnOnce(&mut World, Entity) -> Option<Entity>) -> u16 {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let player = app
+            .world_mut()
+            .spawn((Player { number: 0 }, PlayerBundle::default(), TurnPlayer))
+            .id();
+        let card = place(app.world_mut(), player);
+        let add_gold = EffectDef::AddGold {
+            player: SelectorDef {
+                kind: SelectorKindDef::Player,
+                cardinality: CardinalityDef::Single,
+                selection: SelectionDef::Owner,
+                filters: vec![],
+            },
+            amount: ValueDef::Constant(1),
+        };
+        if let Some(card) = card {
+            app.world_mut().spawn((
+                hook(HookDef::TurnEnded, HookPoint::After, vec![add_gold]),
+                Action { caster: card },
+            ));
+        }
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<NextState<TurnState>>()
+            .set(TurnState::EndTurn);
+        for _ in 0..4 {
+            app.update();
+        }
+        app.world().get::<PlayerResources>(player).unwrap().gold
+    }
+
+    #[test]
+    fn turn_ended_hooks_run_when_the_turn_ends() {
+        let gold = gold_after_turn_end(|world, player| {
+            let tile = world.spawn_empty().id();
+            Some(
+                world
+                    .spawn((Owner(player), OnBoard { position: tile }))
+                    .id(),
+            )
+        });
+        assert_eq!(gold, 11);
+    }
 
-pub fn run_hook<T: HookEvent>(hm: On<T>, mut commands: Commands) {
-    commands.entity(hm.event_target()).trigger(Execute);
+    #[test]
+    fn hooks_of_cards_in_hand_do_not_run() {
+        let gold = gold_after_turn_end(|world, player| {
+            Some(world.spawn((Owner(player), InHand { parent: player })).id())
+        });
+        assert_eq!(gold, gold_after_turn_end(|_, _| None));
+    }
 }
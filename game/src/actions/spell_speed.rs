@@ -7,6 +7,31 @@ use serde::{Deserialize, Serialize};
 pub enum SpellSpeed {
     #[default]
     Slow = 1, // Can only be cast during main phase, when stack is empty
-    Fast = 2,    // Can be cast anytime you have priority
+    Fast = 2,    // Can be cast during your own turn, even in response
     Instant = 3, // Can be cast anytime, even during opponent's turn
 }
+
+impl SpellSpeed {
+    /// Whether a player may play something this fast, given whose turn it is
+    /// and whether abilities are waiting on the stack.
+    pub fn allows(self, own_turn: bool, stack_empty: bool) -> bool {
+        match self {
+            SpellSpeed::Slow => own_turn && stack_empty,
+            SpellSpeed::Fast => own_turn,
+            SpellSpeed::Instant => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_instants_can_be_cast_on_the_opponents_turn() {
+        assert!(SpellSpeed::Fast.allows(true, false));
+        assert!(!SpellSpeed::Fast.allows(false, false));
+        assert!(!SpellSpeed::Fast.allows(false, true));
+        assert!(SpellSpeed::Instant.allows(false, false));
+    }
+}
@@ -0,0 +1,420 @@
+//! The effect stack. Started abilities wait here, last in first out, until
+//! every player has passed priority in succession; only then does the top
+//! ability resolve. Fast and Instant cards may be cast in between to respond.
+
+use bevy::{
+    ecs::{relationship::RelationshipTarget, system::SystemParam},
+    prelude::*,
+};
+
+use crate::{
+    actions::{
+        AbilityData, Actions,
+        execute::{AbilityCursor, Activation, AwaitingChoice},
+        spell_speed::SpellSpeed,
+    },
+    card::{Cost, InHand, SpellCard},
+    components::Owner,
+    def::trigger::TriggerDef,
+    error::GameError,
+    mulligan::Mulligan,
+    player::{Acting, Hand, Player, PlayerResources, TurnPlayer},
+};
+
+/// Activations with a running `AbilityCursor`, bottom first.
+#[derive(Resource, Debug, Default)]
+pub struct EffectStack(Vec<Entity>);
+
+impl EffectStack {
+    /// The ability that resolves next.
+    pub fn top(&self) -> Option<Entity> {
+        self.0.last().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The abilities on the stack, bottom first.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+/// Who may respond to the top of the stack, and how many players have passed
+/// since it was last answered.
+#[derive(Resource, Debug, Default)]
+pub struct Priority {
+    holder: Option<Entity>,
+    passes: usize,
+}
+
+impl Priority {
+    pub fn holder(&self) -> Option<Entity> {
+        self.holder
+    }
+
+    pub fn passes(&self) -> usize {
+        self.passes
+    }
+
+    /// Priority as it stood in a saved game.
+    pub(crate) fn restore(holder: Option<Entity>, passes: usize) -> Self {
+        Self { holder, passes }
+    }
+
+    /// Whether the top of the stack may resolve. Nobody holding priority
+    /// means nobody can respond.
+    pub fn all_passed(&self, players: usize) -> bool {
+        self.holder.is_none() || self.passes >= players
+    }
+
+    fn give(&mut self, player: Option<Entity>) {
+        self.holder = player;
+        self.passes = 0;
+    }
+
+    fn pass(&mut self, next: Option<Entity>) {
+        self.holder = next;
+        self.passes += 1;
+    }
+}
+
+/// The priority holder declines to respond.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct PassPriority {
+    pub player: Entity,
+}
+
+/// Looks up the speed cards are played at and whether a player may play at
+/// that speed right now.
+#[derive(SystemParam)]
+pub struct CardSpeeds<'w, 's> {
+    turn_player: Query<'w, 's, Entity, With<TurnPlayer>>,
+    card_actions: Query<'w, 's, &'static Actions>,
+    abilities: Query<'w, 's, &'static AbilityData>,
+}
+
+impl CardSpeeds<'_, '_> {
+    /// A card is as slow as its slowest `OnPlay` ability.
+    pub fn card_speed(&self, card: Entity) -> SpellSpeed {
+        self.card_actions
+            .get(card)
+            .into_iter()
+            .flat_map(|actions| actions.iter())
+            .filter_map(|ability| self.abilities.get(ability).ok())
+            .filter(|ability| ability.0.trigger == TriggerDef::OnPlay)
+            .map(|ability| ability.0.speed)
+            .min()
+            .unwrap_or_default()
+    }
+
+    pub fn allows(&self, player: Entity, speed: SpellSpeed, stack_empty: bool) -> bool {
+        let own_turn = self.turn_player.single().is_ok_and(|turn| turn == player);
+        speed.allows(own_turn, stack_empty)
+    }
+}
+
+/// Everything needed to check whether a card may be played right now.
+#[derive(SystemParam)]
+pub struct PlayWindow<'w, 's> {
+    stack: Res<'w, EffectStack>,
+    priority: Res<'w, Priority>,
+    speeds: CardSpeeds<'w, 's>,
+}
+
+impl PlayWindow<'_, '_> {
+    pub fn card_speed(&self, card: Entity) -> SpellSpeed {
+        self.speeds.card_speed(card)
+    }
+
+    /// While the stack is not empty only the priority holder may play, and
+    /// only at a speed that allows responding.
+    pub fn check(&self, player: Entity, speed: SpellSpeed) -> Result<(), GameError> {
+        let stack_empty = self.stack.is_empty();
+        if !stack_empty && self.priority.holder() != Some(player) {
+            return Err(GameError::NoPriority);
+        }
+        if !self.speeds.allows(player, speed, stack_empty) {
+            return Err(GameError::TooSlow(speed));
+        }
+        Ok(())
+    }
+}
+
+/// The ability at the top of the stack, once every player has passed on it.
+#[derive(SystemParam)]
+pub struct ResolvingAbility<'w, 's> {
+    stack: Res<'w, EffectStack>,
+    priority: Res<'w, Priority>,
+    players: Query<'w, 's, (), With<Player>>,
+    cursors: Query<'w, 's, (&'static mut AbilityCursor, &'static Activation), Without<AwaitingChoice>>,
+}
+
+impl ResolvingAbility<'_, '_> {
+    /// `None` while players may still respond or the top ability waits on a
+    /// choice.
+    pub fn get_mut(&mut self) -> Option<(Entity, Mut<'_, AbilityCursor>, &Activation)> {
+        if !self.priority.all_passed(self.players.iter().count()) {
+            return None;
+        }
+        let top = self.stack.top()?;
+        let (cursor, action) = self.cursors.get_mut(top).ok()?;
+        Some((top, cursor, action))
+    }
+}
+
+/// Pushes a freshly started ability onto the stack. The opponent of its
+/// controller gets the first chance to respond.
+pub fn push_ability(
+    add: On<Add, AbilityCursor>,
+    activations: Query<&Activation>,
+    owners: Query<&Owner>,
+    players: Query<(Entity, &Player)>,
+    turn_player: Query<Entity, With<TurnPlayer>>,
+    mut stack: ResMut<EffectStack>,
+    mut priority: ResMut<Priority>,
+) {
+    stack.0.push(add.entity);
+
+    let controller = activations
+        .get(add.entity)
+        .ok()
+        .and_then(|activation| owners.get(activation.caster).ok())
+        .map(|owner| owner.0);
+    let responder = controller
+        .and_then(|controller| next_seat(&players, controller))
+        .or(turn_player.single().ok());
+    priority.give(responder);
+}
+
+/// Takes a resolved or cancelled ability off the stack. Whatever remains
+/// below it is open to responses again, starting with the turn player.
+pub fn pop_ability(
+    remove: On<Remove, AbilityCursor>,
+    turn_player: Query<Entity, With<TurnPlayer>>,
+    mut stack: ResMut<EffectStack>,
+    mut priority: ResMut<Priority>,
+) {
+    stack.0.retain(|&ability| ability != remove.entity);
+    if stack.is_empty() {
+        priority.give(None);
+    } else {
+        priority.give(turn_player.single().ok());
+    }
+}
+
+/// Hands priority around the table. Players without an affordable card fast
+/// enough to respond pass automatically.
+pub fn pass_priority(
+    mut passes: MessageReader<PassPriority>,
+    mut priority: ResMut<Priority>,
+    stack: Res<EffectStack>,
+    players: Query<(Entity, &Player)>,
+    hands: Query<(&Hand, &PlayerResources)>,
+    spells: Query<&Cost, (With<SpellCard>, With<InHand>)>,
+    speeds: CardSpeeds,
+) {
+    if stack.is_empty() {
+        passes.clear();
+        return;
+    }
+
+    for pass in passes.read() {
+        if priority.holder() == Some(pass.player) {
+            priority.pass(next_seat(&players, pass.player));
+        }
+    }
+
+    let seats = players.iter().count();
+    while !priority.all_passed(seats) {
+        let Some(holder) = priority.holder() else {
+            break;
+        };
+        let can_respond = hands.get(holder).is_ok_and(|(hand, resources)| {
+            hand.iter().any(|card| {
+                spells
+                    .get(card)
+                    .is_ok_and(|cost| cost.value <= resources.gold)
+                    && speeds.allows(holder, speeds.card_speed(card), false)
+            })
+        });
+        if can_respond {
+            break;
+        }
+        priority.pass(next_seat(&players, holder));
+    }
+}
+
+/// Keeps `Acting` on the player who makes the next decision: the first
+/// player still deciding on a mulligan, the priority holder while the stack
+/// is not empty, the turn player otherwise.
+pub fn sync_acting_player(
+    stack: Res<EffectStack>,
+    priority: Res<Priority>,
+    turn_player: Query<Entity, With<TurnPlayer>>,
+    mulligan: Query<(Entity, &Player), With<Mulligan>>,
+    acting: Query<Entity, With<Acting>>,
+    mut commands: Commands,
+) {
+    let turn_player = turn_player.single().ok();
+    let deciding = mulligan
+        .iter()
+        .min_by_key(|(_, seat)| seat.number)
+        .map(|(player, _)| player);
+    let wanted = if deciding.is_some() {
+        deciding
+    } else if stack.is_empty() {
+        turn_player
+    } else {
+        priority.holder().or(turn_player)
+    };
+
+    for player in &acting {
+        if Some(player) != wanted {
+            commands.entity(player).remove::<Acting>();
+        }
+    }
+    if let Some(player) = wanted
+        && !acting.contains(player)
+    {
+        commands.entity(player).insert(Acting);
+    }
+}
+
+/// The player seated after `player`, wrapping around.
+fn next_seat(players: &Query<(Entity, &Player)>, player: Entity) -> Option<Entity> {
+    let mut seats: Vec<_> = players.iter().collect();
+    seats.sort_by_key(|(_, seat)| seat.number);
+    let current = seats.iter().position(|(entity, _)| *entity == player)?;
+    let (next, _) = seats[(current + 1) % seats.len()];
+    Some(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GameRng,
+        actions::{Action, ActionPlugin},
+        def::{
+            condition::ConditionDef,
+            effect::EffectDef,
+            selector::{CardinalityDef, SelectionDef, SelectorDef, SelectorKindDef},
+            trigger::AbilityDef,
+            value::ValueDef,
+        },
+        events::GameMessagesPlugin,
+        player::PlayerBundle,
+        turn_controller::TurnControllerPlugin,
+    };
+    use bevy::state::app::StatesPlugin;
+
+    fn ability(speed: SpellSpeed, effects: Vec<EffectDef>) -> AbilityData {
+        AbilityData(AbilityDef {
+            trigger: TriggerDef::OnPlay,
+            condition: ConditionDef::Always,
+            speed,
+            timing: Default::default(),
+            effects,
+            replacement: None,
+        })
+    }
+
+    #[test]
+    fn ability_waits_until_every_player_passed() {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let turn_player = app
+            .world_mut()
+            .spawn((Player { number: 0 }, PlayerBundle::default(), TurnPlayer))
+            .id();
+        let opponent = app
+            .world_mut()
+            .spawn((Player { number: 1 }, PlayerBundle::default()))
+            .id();
+
+        // The opponent holds an affordable Instant, so they are asked.
+        let instant = app
+            .world_mut()
+            .spawn((
+                SpellCard,
+                Cost { value: 1 },
+                Owner(opponent),
+                InHand { parent: opponent },
+            ))
+            .id();
+        app.world_mut().spawn((
+            ability(SpellSpeed::Instant, vec![]),
+            Action { caster: instant },
+        ));
+
+        let caster = app.world_mut().spawn(Owner(turn_player)).id();
+        let add_gold = EffectDef::AddGold {
+            player: SelectorDef {
+                kind: SelectorKindDef::Player,
+                cardinality: CardinalityDef::Single,
+                selection: SelectionDef::Owner,
+                filters: vec![],
+            },
+            amount: ValueDef::Constant(1),
+        };
+        let pending = app
+            .world_mut()
+            .spawn((
+                ability(SpellSpeed::Slow, vec![add_gold.clone()]),
+                Action { caster },
+            ))
+            .id();
+        app.world_mut().spawn((
+            Activation {
+                ability: pending,
+                caster,
+            },
+            ChildOf(pending),
+            AbilityCursor {
+                stack: vec![add_gold],
+                context: Default::default(),
+            },
+        ));
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<Priority>().holder(), Some(opponent));
+        assert_eq!(
+            app.world()
+                .get::<PlayerResources>(turn_player)
+                .unwrap()
+                .gold,
+            10
+        );
+
+        app.world_mut()
+            .write_message(PassPriority { player: opponent });
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world()
+                .get::<PlayerResources>(turn_player)
+                .unwrap()
+                .gold,
+            11
+        );
+        assert!(app.world().resource::<EffectStack>().is_empty());
+    }
+}
@@ -13,6 +13,7 @@ use crate::{
         value_source::{ValueEvalParams, ValueSource},
     },
     board::tile::Tile,
+    components::Owner,
 };
 
 pub mod filters;
@@ -439,24 +440,24 @@ where
     }
 }
 
-fn select_friendly(q_creatures: &Query<CreatureQuery>, caster: Entity) -> Vec<Entity> {
-    let Ok(caster_creature) = q_creatures.get(caster) else {
+fn select_friendly(params: &ValueEvalParams, caster: Entity) -> Vec<Entity> {
+    let Ok(owner_of_caster) = params.owners.get(caster) else {
         return Vec::new();
     };
-    let owner_of_caster = caster_creature.owner;
-    q_creatures
+    params
+        .creatures
         .iter()
         .filter(|q| q.owner == owner_of_caster)
         .map(|q| q.entity)
         .collect()
 }
 
-fn select_enemy(q_creatures: &Query<CreatureQuery>, caster: Entity) -> Vec<Entity> {
-    let Ok(caster_creature) = q_creatures.get(caster) else {
+fn select_enemy(params: &ValueEvalParams, caster: Entity) -> Vec<Entity> {
+    let Ok(owner_of_caster) = params.owners.get(caster) else {
         return Vec::new();
     };
-    let owner_of_caster = caster_creature.owner;
-    q_creatures
+    params
+        .creatures
         .iter()
         .filter(|q| q.owner != owner_of_caster)
         .map(|q| q.entity)
@@ -555,8 +556,8 @@ impl IsTargetSelectMode for AutoMultiTile {
 impl IsTargetSelectMode for AutoMultiCreature {
     fn find_suitable(&self, params: &mut ValueEvalParams, caster: Entity) -> Vec<Entity> {
         match self {
-            AutoMultiCreature::AllEnemy => select_enemy(params.creatures, caster),
-            AutoMultiCreature::AllFriendly => select_friendly(params.creatures, caster),
+            AutoMultiCreature::AllEnemy => select_enemy(params, caster),
+            AutoMultiCreature::AllFriendly => select_friendly(params, caster),
             AutoMultiCreature::Random { count: _ } => {
                 let mut all_creatures = select_all_creatures(params.creatures);
                 all_creatures.shuffle(&mut params.rng.0);
@@ -591,7 +592,7 @@ impl IsTargetSelectMode for ManualCreature {
         match self {
             ManualCreature::Choose { .. } => select_all_creatures(params.creatures),
             ManualCreature::MaxNFriendly { .. } | ManualCreature::ExactlyNFriendly { .. } => {
-                select_friendly(params.creatures, caster)
+                select_friendly(params, caster)
             }
         }
     }
@@ -629,10 +630,9 @@ impl IsTargetSelectMode for AutoPlayerSingle {
                     .collect()
             }
             AutoPlayerSingle::Owner => {
-                let Ok(caster_creature) = params.creatures.get(caster) else {
+                let Ok(&Owner(owner)) = params.owners.get(caster) else {
                     return Vec::new();
                 };
-                let owner = caster_creature.owner.0;
                 params
                     .player
                     .iter()
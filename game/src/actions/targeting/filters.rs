@@ -10,7 +10,7 @@ use crate::{
         value_source::{ValueEvalParams, ValueSource},
     },
     board::{effect::EffectType, tile::Tile},
-
+    components::Owner,
 };
 
 pub trait IsFilter {
@@ -126,9 +126,8 @@ impl IsFilter for CreatureFilters {
         }
 
         if let Some(can_attack) = self.can_attack {
-            // There is no dedicated CanAttack component yet. A missing flag is
-            // treated as "can attack", so only a positive filter passes.
-            if !can_attack {
+            let able = !creature.attacked && creature.current_atttack.0 > 0;
+            if able != can_attack {
                 return false;
             }
         }
@@ -383,4 +382,5 @@ pub struct FilterParams<'w, 's> {
     pub hand: Query<'w, 's, HandQuery>,
     pub player: Query<'w, 's, PlayerQuery>,
     pub effects: Query<'w, 's, &'static EffectType>,
+    pub owners: Query<'w, 's, &'static Owner>,
 }
@@ -4,7 +4,7 @@ use bevy::{
         component::Component,
         entity::Entity,
         hierarchy::{ChildOf, Children},
-        query::{QueryData, With},
+        query::{Has, QueryData, With},
         resource::Resource,
         schedule::{IntoScheduleConfigs, common_conditions::any_with_component},
         system::{Commands, Query},
@@ -22,6 +22,7 @@ use crate::{
         },
         value_source::ValueSource,
     },
+    board::combat::AttackedThisTurn,
     board::tile::Occupant,
     board::tile::Position,
     card::{
@@ -62,12 +63,14 @@ pub struct CreatureQuery {
     pub entity: Entity,
     pub owner: &'static Owner,
     pub position: &'static OnBoard,
+    pub attacked: Has<AttackedThisTurn>,
 }
 
 #[derive(Debug, QueryData)]
 pub struct TileQuery {
     pub entity: Entity,
-    pub children: &'static Children,
+    /// The effects lying on the tile, if any.
+    pub children: Option<&'static Children>,
     pub position: &'static Position,
     pub occupant: Option<&'static Occupant>,
 }
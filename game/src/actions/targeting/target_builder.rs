@@ -54,6 +54,16 @@ where
     }
 }
 
+impl<K, C> Default for TargetSelectorBuilder<K, C, UnsetCardinality, UnsetSelection>
+where
+    C: Constraint,
+    K: TargetKind<C>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<K, Card, Sel> TargetSelectorBuilder<K, SingleTarget, Card, Sel>
 where
     K: TargetKind<SingleTarget>,
@@ -16,6 +16,7 @@ use crate::{
         systems::{CreatureQuery, HandQuery, PlayerQuery, TileQuery},
     },
     board::{effect::EffectType, tile::Tile},
+    components::Owner,
 };
 
 use super::targeting::{CreatureTarget, MultiTargetSelector, SingleTarget, TargetSelector};
@@ -64,6 +65,7 @@ pub struct ValueEvalParams<'a, 'w, 's> {
     pub hand: &'a Query<'w, 's, HandQuery>,
     pub player: &'a Query<'w, 's, PlayerQuery>,
     pub effects: &'a Query<'w, 's, &'static EffectType>,
+    pub owners: &'a Query<'w, 's, &'static Owner>,
     pub rng: &'a mut GameRng,
     pub current_target: Option<Entity>,
 }
@@ -76,6 +78,7 @@ impl<'w, 's> FilterParams<'w, 's> {
             hand: &self.hand,
             player: &self.player,
             effects: &self.effects,
+            owners: &self.owners,
             rng,
             current_target: None,
         }
@@ -172,36 +175,6 @@ pub enum StatModifier {
     Both { attack: i16, health: i16 },
 }
 
-fn apply_delta(value: &mut u16, delta: i16) {
-    if delta >= 0 {
-        *value = value.saturating_add(delta as u16);
-    } else {
-        *value = value.saturating_sub((-delta) as u16);
-    }
-}
-
-impl StatModifier {
-    /// Apply this modifier to raw stat values.
-    pub fn apply(
-        &self,
-        attack: &mut u16,
-        health: &mut u16,
-        max_health: &mut u16,
-        speed: &mut u16,
-    ) {
-        match *self {
-            StatModifier::Attack(d) => apply_delta(attack, d),
-            StatModifier::Health(d) => apply_delta(health, d),
-            StatModifier::MaxHealth(d) => apply_delta(max_health, d),
-            StatModifier::Speed(d) => apply_delta(speed, d),
-            StatModifier::Both { attack: a, health: h } => {
-                apply_delta(attack, a);
-                apply_delta(health, h);
-            }
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StatType {
     Attack,
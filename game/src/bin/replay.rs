@@ -0,0 +1,34 @@
+//! Plays a saved game log again without a window and checks that the game
+//! ends the way it did: `cargo run --bin replay -- game.log.ron`.
+
+use std::process::ExitCode;
+
+use game::replay::{GameLog, verify};
+
+fn main() -> ExitCode {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: replay <game log>");
+        return ExitCode::FAILURE;
+    };
+    let log = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| ron::de::from_str::<GameLog>(&text).map_err(|e| e.to_string()));
+    let log = match log {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match verify(&log) {
+        Ok(()) => {
+            println!("{}: {} intents replayed to the same result", path, log.intents.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            ExitCode::FAILURE
+        }
+    }
+}
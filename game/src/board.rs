@@ -8,27 +8,34 @@ use effect::Effect;
 use place_error::BoardError;
 
 use crate::{
+    actions::{AbilityData, Action, execute::AbilityStarter},
     board::{
+        combat::{AttackRequest, handle_attack, reset_attacks},
         effect::*,
         movement::*,
-        placement::{CardPlayed, place_card},
+        placement::{CardPlayed, cast_spell, place_card, set_trap},
+        trap::{reveal_on_condition, reveal_on_enter},
         tile::*,
     },
     card::{
-        OnBoard,
+        CreatureCard, OnBoard,
         creature::{AttackPattern, Attacks},
     },
     components::{Health, Owner},
+    def::{loader::LoadState, trigger::TriggerDef},
     events::{EffectAdded, EffectRemoved},
-    player::Player,
-    turn_controller::{BoardClicked, TurnPhase},
+    player::{Player, TurnPlayer, add_player},
+    rules::Ruleset,
+    turn_controller::TurnPhase,
 };
 
+pub mod combat;
 pub mod effect;
 pub mod movement;
 pub mod place_error;
 pub mod placement;
 pub mod tile;
+pub mod trap;
 
 #[derive(Bundle)]
 pub struct PlayerBaseBundle {
@@ -37,10 +44,10 @@ pub struct PlayerBaseBundle {
 }
 
 impl PlayerBaseBundle {
-    fn new() -> Self {
+    fn new(health: u16) -> Self {
         Self {
             player_base: PlayerBase,
-            health: Health::player_base_health(),
+            health: Health(health),
         }
     }
 }
@@ -59,35 +66,20 @@ pub struct BoardRes {
 }
 
 impl BoardRes {
-    pub const XSIZE: u16 = 24;
-    pub const YSIZE: u16 = 12;
-    pub fn setup_board(mut commands: Commands) {
+    pub fn setup_board(mut commands: Commands, rules: Res<Ruleset>) {
         let mut tiles = HashMap::new();
+        let size = rules.board_size();
         let player_base_positions = [
-            U16Vec2::new(2, Self::YSIZE / 2),
-            U16Vec2::new(Self::XSIZE - 3, Self::YSIZE / 2),
+            U16Vec2::new(2, size.y / 2),
+            U16Vec2::new(size.x.saturating_sub(3), size.y / 2),
         ];
         let board_id = commands.spawn((Board,)).id();
 
-        for x in 0..Self::XSIZE {
-            for y in 0..Self::YSIZE {
+        for x in 0..size.x {
+            for y in 0..size.y {
                 let position = U16Vec2::new(x, y);
                 let tile_id = commands
                     .spawn((TileBundel::default(), ChildOf(board_id), Position(position)))
-                    .observe(
-                        |click: On<Pointer<Release>>,
-                         mut board_clicked: MessageWriter<BoardClicked>,
-                         tiles: Query<&Position, With<Tile>>| {
-                            info!("Tile clicked");
-                            let &Position(position) = tiles
-                                .get(click.entity)
-                                .expect("Clicked thing is somehow not a tile");
-                            board_clicked.write(BoardClicked {
-                                position,
-                                entity: click.entity,
-                            });
-                        },
-                    )
                     .id();
                 tiles.insert(position, tile_id);
             }
@@ -95,7 +87,7 @@ impl BoardRes {
 
         commands.insert_resource(BoardRes {
             tiles,
-            size: U16Vec2::new(Self::XSIZE, Self::YSIZE),
+            size,
             player_base_positions,
         });
     }
@@ -103,16 +95,21 @@ impl BoardRes {
     pub fn setup_player_bases(
         mut commands: Commands,
         board: Res<BoardRes>,
-        players: Query<Entity, With<Player>>,
+        players: Query<(Entity, &Player)>,
+        rules: Res<Ruleset>,
     ) {
-        for (player_entity, pos) in players.iter().zip(board.player_base_positions.into_iter()) {
+        for (player_entity, player) in &players {
+            let Some(&pos) = board.player_base_positions.get(player.number as usize) else {
+                warn!("No base position for player {}", player.number);
+                continue;
+            };
             let tile = board
                 .get_tile(&pos)
                 .ok_or(BoardError::TileNotFound)
                 .expect("This is a setup error and should never happen");
             let _base_entity = commands
                 .spawn((
-                    PlayerBaseBundle::new(),
+                    PlayerBaseBundle::new(rules.base_health),
                     Owner(player_entity),
                     OnBoard { position: tile },
                 ))
@@ -170,18 +167,35 @@ pub struct EffectRequested {
     pub indices: Vec<U16Vec2>,
 }
 
+/// Lays requested effects on their tiles. Effects with per-turn effects get
+/// an ability, owned by the effect, that runs them.
 fn add_effect_to_tile(
     mut commands: Commands,
     board: Res<BoardRes>,
+    registry: Res<EffectRegistry>,
     mut effects: MessageReader<EffectRequested>,
     mut effects_added: MessageWriter<EffectAdded>,
 ) -> Result {
     for effect_play_event in effects.read() {
+        let effect_type = effect_play_event.effect.effect_type();
+        let Some(def) = registry.get(effect_type) else {
+            warn!("Unknown tile effect {:?}", effect_type);
+            continue;
+        };
         for index in &effect_play_event.indices {
             let tile = board.get_tile(index).ok_or(BoardError::Index)?;
-            commands.spawn((ChildOf(tile), effect_play_event.effect));
+            let effect = commands
+                .spawn((ChildOf(tile), effect_play_event.effect.clone()))
+                .id();
+            if let Some(ability) = def.per_turn_ability() {
+                commands.spawn((
+                    AbilityData(ability),
+                    Action { caster: effect },
+                    ChildOf(effect),
+                ));
+            }
             effects_added.write(EffectAdded {
-                effect: effect_play_event.effect,
+                effect: effect_play_event.effect.clone(),
                 tile,
             });
         }
@@ -189,23 +203,60 @@ fn add_effect_to_tile(
     Ok(())
 }
 
+/// Counts effect durations down at the start of a turn, on every turn or only
+/// on their owner's, and removes effects that ran out.
 pub fn decrease_effect_duration(
     mut commands: Commands,
-    mut effects: Query<(&mut EffectDuration, &EffectType, Entity, &ChildOf), With<EffectType>>,
+    mut effects: Query<(&mut EffectDuration, &EffectType, &Owner, Entity, &ChildOf)>,
+    turn_player: Query<Entity, With<TurnPlayer>>,
+    registry: Res<EffectRegistry>,
     mut effect_removed: MessageWriter<EffectRemoved>,
 ) {
-    for (mut duration, effect_type, effect_entity, tile) in effects.iter_mut() {
+    let turn_player = turn_player.single().ok();
+    for (mut duration, effect_type, owner, effect_entity, tile) in effects.iter_mut() {
+        let ticks = registry
+            .get(effect_type)
+            .map(|def| def.ticks)
+            .unwrap_or_default();
+        if ticks == EffectTicks::OwnersTurn && turn_player != Some(owner.0) {
+            continue;
+        }
         duration.decrease();
         if duration.over() {
             commands.entity(effect_entity).despawn();
             effect_removed.write(EffectRemoved {
-                effect: *effect_type,
+                effect: effect_type.clone(),
                 tile: tile.0,
             });
         }
     }
 }
 
+/// Runs the per-turn effects of every effect lying under a creature of the
+/// turn player it affects, with that creature as their target.
+pub fn start_tile_effects(
+    creatures: Query<(Entity, &Owner, &OnBoard), With<CreatureCard>>,
+    turn_player: Query<Entity, With<TurnPlayer>>,
+    effects: Query<(Entity, &EffectType, &Owner, &ChildOf)>,
+    registry: Res<EffectRegistry>,
+    mut starter: AbilityStarter,
+) {
+    let Ok(player) = turn_player.single() else {
+        return;
+    };
+    for (creature, owner, on_board) in &creatures {
+        if owner.0 != player {
+            continue;
+        }
+        for (effect, effect_type, effect_owner, tile) in &effects {
+            if tile.0 == on_board.position && registry.affects(effect_type, effect_owner.0, player)
+            {
+                starter.start(effect, TriggerDef::OnTurnStart, Some(creature));
+            }
+        }
+    }
+}
+
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
@@ -214,26 +265,91 @@ impl Plugin for BoardPlugin {
             // Register the Messages (events)
             .add_message::<EffectRequested>()
             .add_message::<MoveRequest>()
+            .add_message::<AttackRequest>()
+            .add_observer(reveal_on_enter)
             .add_message::<CardPlayed>()
-            // Setup systems (run once at startup)
+            .init_resource::<EffectRegistry>()
+            .init_resource::<Ruleset>()
+            // Setup systems (run once the ruleset is loaded)
             .add_systems(
-                Startup,
+                OnEnter(LoadState::Ready),
                 (
                     BoardRes::setup_board,
-                    BoardRes::setup_player_bases.after(BoardRes::setup_board),
+                    BoardRes::setup_player_bases
+                        .after(BoardRes::setup_board)
+                        .after(add_player),
                 ),
             )
             .add_systems(
                 Update,
                 (
                     handle_movement,
+                    handle_attack,
                     add_effect_to_tile,
-                    decrease_effect_duration,
                     place_card,
+                    cast_spell,
+                    set_trap,
+                    reveal_on_condition,
                     update_attack_values,
-                ),
+                )
+                    // The board is set up once the ruleset is loaded.
+                    .run_if(in_state(LoadState::Ready)),
             )
             // System that runs at the start of each turn
-            .add_systems(OnEnter(TurnPhase::Start), refresh_movement_points);
+            .add_systems(
+                OnEnter(TurnPhase::Start),
+                (
+                    refresh_movement_points,
+                    reset_attacks,
+                    (
+                        decrease_effect_duration,
+                        start_tile_effects,
+                    )
+                        .chain(),
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::tile_effect::TileEffectDef;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn effects_ticking_on_their_owners_turn_wait_for_it() {
+        let mut world = World::new();
+        world.init_resource::<Messages<EffectRemoved>>();
+        let mut registry = EffectRegistry::default();
+        let [burning, shielded] = [EffectTicks::EveryTurn, EffectTicks::OwnersTurn].map(|ticks| {
+            registry.insert(TileEffectDef {
+                name: EffectType::new(format!("{ticks:?}")),
+                ticks,
+                ..Default::default()
+            })
+        });
+        world.insert_resource(registry);
+
+        world.spawn(TurnPlayer);
+        let opponent = world.spawn_empty().id();
+        let tile = world.spawn_empty().id();
+        let every_turn = world
+            .spawn((
+                ChildOf(tile),
+                Effect::new(burning, 2, opponent),
+            ))
+            .id();
+        let owners_turn = world
+            .spawn((
+                ChildOf(tile),
+                Effect::new(shielded, 2, opponent),
+            ))
+            .id();
+
+        world.run_system_once(decrease_effect_duration).unwrap();
+
+        assert_eq!(world.get::<EffectDuration>(every_turn).unwrap().0, 1);
+        assert_eq!(world.get::<EffectDuration>(owners_turn).unwrap().0, 2);
     }
 }
@@ -0,0 +1,416 @@
+use std::fmt::Display;
+
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        message::{Message, MessageReader, MessageWriter},
+        query::{Has, QueryData, With},
+        system::{Commands, Query},
+        world::World,
+    },
+    log::{info, warn},
+};
+
+use crate::{
+    actions::{DealDamage, hooks::TriggerHookedExt},
+    board::{PlayerBase, effect::Tiles, place_error::BoardError},
+    card::{
+        CreatureCard, CurrentAttack, CurrentDefense, OnBoard, abilities::CardAbilities,
+        creature::Attacks,
+    },
+    components::Owner,
+    events::CreatureAttacked,
+    player::TurnPlayer,
+};
+
+#[derive(Message)]
+pub struct AttackRequest {
+    pub attacker: Entity,
+    pub target: Entity,
+}
+
+#[derive(QueryData)]
+pub struct Combatant {
+    pub attack: &'static CurrentAttack,
+    pub defense: &'static CurrentDefense,
+    pub attacks: &'static Attacks,
+    pub owner: &'static Owner,
+    pub tile: &'static OnBoard,
+    pub attacked: Has<AttackedThisTurn>,
+    pub abilities: Option<&'static CardAbilities>,
+}
+
+/// Marks a creature that already attacked during the current turn.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AttackedThisTurn;
+
+#[derive(Debug)]
+pub enum AttackValidationError {
+    NotTurnPlayer,
+    AlreadyAttacked,
+    FriendlyTarget,
+    OutOfRange,
+    /// The target is Digging and the attacker is not adjacent to it.
+    Burrowed,
+}
+
+impl Display for AttackValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttackValidationError::NotTurnPlayer => {
+                write!(f, "Attacker does not belong to the turn player")
+            }
+            AttackValidationError::AlreadyAttacked => {
+                write!(f, "Attacker already attacked this turn")
+            }
+            AttackValidationError::FriendlyTarget => write!(f, "Cannot attack a friendly target"),
+            AttackValidationError::OutOfRange => write!(f, "Target is not in attack range"),
+            AttackValidationError::Burrowed => {
+                write!(f, "Digging targets can only be attacked from an adjacent tile")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttackValidationError {}
+
+/// Resolves attacks on creatures and player bases. The attacker deals its
+/// current attack to the target; a creature that survives the damage, as
+/// changed or prevented by hooks, and has the attacker in its own attack
+/// range strikes back with its current attack. Bases never strike back.
+/// Digging creatures can only be attacked from an adjacent tile.
+/// The effects on a creature's tile modify the attack it strikes with and
+/// the damage it takes. Invalid requests are skipped with a warning.
+pub fn handle_attack(
+    mut commands: Commands,
+    mut attack_requests: MessageReader<AttackRequest>,
+    mut attacked: MessageWriter<CreatureAttacked>,
+    creatures: Query<Combatant, With<CreatureCard>>,
+    bases: Query<(&Owner, &OnBoard), With<PlayerBase>>,
+    tiles: Tiles,
+    turn_player: Query<Entity, With<TurnPlayer>>,
+) {
+    for request in attack_requests.read() {
+        let Ok(attacker) = creatures.get(request.attacker) else {
+            skip_attack(request, BoardError::CardNotFound);
+            continue;
+        };
+        let defender = creatures.get(request.target).ok();
+        let (defender_owner, defender_tile) = match &defender {
+            Some(creature) => (creature.owner, creature.tile),
+            None => match bases.get(request.target) {
+                Ok(base) => base,
+                Err(_) => {
+                    skip_attack(request, BoardError::CardNotFound);
+                    continue;
+                }
+            },
+        };
+
+        if turn_player.single().ok() != Some(attacker.owner.0) {
+            skip_attack(request, AttackValidationError::NotTurnPlayer);
+            continue;
+        }
+        if attacker.attacked {
+            skip_attack(request, AttackValidationError::AlreadyAttacked);
+            continue;
+        }
+        if attacker.owner == defender_owner {
+            skip_attack(request, AttackValidationError::FriendlyTarget);
+            continue;
+        }
+
+        let (Ok(attacker_pos), Ok(defender_pos)) = (
+            tiles.position(attacker.tile.position),
+            tiles.position(defender_tile.position),
+        ) else {
+            skip_attack(request, BoardError::TileNotFound);
+            continue;
+        };
+        if !attacker.attacks.0.contains(&defender_pos) {
+            skip_attack(request, AttackValidationError::OutOfRange);
+            continue;
+        }
+        let burrowed = |creature: &CombatantItem| {
+            creature
+                .abilities
+                .is_some_and(CardAbilities::only_adjacent_attacks)
+        };
+        let adjacent = attacker_pos.chebyshev_distance(defender_pos) <= 1;
+        if !adjacent && defender.as_ref().is_some_and(burrowed) {
+            skip_attack(request, AttackValidationError::Burrowed);
+            continue;
+        }
+
+        let attack = tiles
+            .rule(attacker.tile.position, attacker.owner.0)
+            .modify_attack(attacker.attack.0);
+        let damage = tiles
+            .rule(defender_tile.position, defender_owner.0)
+            .modify_damage_taken(attack);
+        info!(
+            "Creature {} attacks {} for {}",
+            request.attacker, request.target, damage
+        );
+        commands.trigger_hooked(
+            DealDamage::new(damage, request.target).with_source(request.attacker),
+        );
+
+        if let Some(defender) = defender {
+            let counter = tiles
+                .rule(defender.tile.position, defender_owner.0)
+                .modify_attack(defender.attack.0);
+            let counter = tiles
+                .rule(attacker.tile.position, attacker.owner.0)
+                .modify_damage_taken(counter);
+            if counter > 0
+                && defender.attacks.0.contains(&attacker_pos)
+                && (adjacent || !burrowed(&attacker))
+            {
+                let (attacker, target) = (request.attacker, request.target);
+                // Runs after the damage and its hooks were applied.
+                commands.queue(move |world: &mut World| {
+                    let survives = world.get::<OnBoard>(target).is_some()
+                        && world.get::<CurrentDefense>(target).is_some_and(|d| d.0 > 0);
+                    if survives {
+                        info!(
+                            "Creature {} retaliates against {} for {}",
+                            target, attacker, counter
+                        );
+                        world.trigger_hooked(
+                            DealDamage::new(counter, attacker).with_source(target),
+                        );
+                    }
+                });
+            }
+        }
+
+        commands.entity(request.attacker).insert(AttackedThisTurn);
+        attacked.write(CreatureAttacked {
+            attacker: request.attacker,
+            target: request.target,
+            damage,
+        });
+    }
+}
+
+fn skip_attack(request: &AttackRequest, error: impl Display) {
+    warn!(
+        "Skipping attack of {} on {}: {}",
+        request.attacker, request.target, error
+    );
+}
+
+/// Lets every creature attack again at the start of a turn.
+pub(crate) fn reset_attacks(
+    mut commands: Commands,
+    creatures: Query<Entity, With<AttackedThisTurn>>,
+) {
+    for creature in &creatures {
+        commands.entity(creature).remove::<AttackedThisTurn>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GameRng,
+        actions::{AbilityData, Action, ActionPlugin},
+        board::{
+            effect::{Affects, EffectRegistry, EffectRule, EffectType},
+            tile::Position,
+        },
+        card::abilities::Abilities,
+        components::Health,
+        def::{
+            condition::ConditionDef,
+            tile_effect::TileEffectDef,
+            trigger::{AbilityDef, HookDef, ReplacementDef, TriggerDef},
+        },
+        events::GameMessagesPlugin,
+        turn_controller::TurnControllerPlugin,
+    };
+    use bevy::{
+        app::{App, Update},
+        input::{ButtonInput, keyboard::KeyCode},
+        math::U16Vec2,
+        prelude::ChildOf,
+        state::app::StatesPlugin,
+    };
+
+    fn spawn_creature(world: &mut World, owner: Entity, at: U16Vec2, target: U16Vec2) -> Entity {
+        let tile = world.spawn(Position(at)).id();
+        world
+            .spawn((
+                CreatureCard,
+                CurrentAttack(2),
+                CurrentDefense(5),
+                Health(5),
+                Attacks(vec![target]),
+                Owner(owner),
+                OnBoard { position: tile },
+            ))
+            .id()
+    }
+
+    fn combat_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .init_resource::<EffectRegistry>()
+        .insert_resource(ButtonInput::<KeyCode>::default())
+        .add_message::<AttackRequest>()
+        .add_systems(Update, handle_attack)
+        .set_error_handler(bevy::ecs::error::ignore);
+        app
+    }
+
+    #[test]
+    fn attack_deals_damage_and_defender_retaliates_once_per_turn() {
+        let mut app = combat_app();
+        let world = app.world_mut();
+        let me = world.spawn(TurnPlayer).id();
+        let enemy = world.spawn_empty().id();
+        let a = U16Vec2::new(0, 0);
+        let b = U16Vec2::new(1, 0);
+        let attacker = spawn_creature(world, me, a, b);
+        let defender = spawn_creature(world, enemy, b, a);
+
+        for _ in 0..2 {
+            app.world_mut().write_message(AttackRequest {
+                attacker,
+                target: defender,
+            });
+            app.update();
+        }
+
+        assert_eq!(app.world().get::<CurrentDefense>(defender).unwrap().0, 3);
+        assert_eq!(app.world().get::<CurrentDefense>(attacker).unwrap().0, 3);
+        assert!(app.world().get::<AttackedThisTurn>(attacker).is_some());
+    }
+
+    #[test]
+    fn digging_creatures_can_only_be_attacked_from_adjacent_tiles() {
+        let mut app = combat_app();
+        let world = app.world_mut();
+        let me = world.spawn(TurnPlayer).id();
+        let enemy = world.spawn_empty().id();
+        let far = U16Vec2::new(2, 0);
+        let attacker = spawn_creature(world, me, U16Vec2::new(0, 0), far);
+        let digger = spawn_creature(world, enemy, far, U16Vec2::new(0, 0));
+        world
+            .entity_mut(digger)
+            .insert(CardAbilities(vec![Abilities::Digging]));
+
+        app.world_mut().write_message(AttackRequest {
+            attacker,
+            target: digger,
+        });
+        app.update();
+
+        assert_eq!(app.world().get::<CurrentDefense>(digger).unwrap().0, 5);
+        assert!(app.world().get::<AttackedThisTurn>(attacker).is_none());
+    }
+
+    #[test]
+    fn invalid_attacks_do_not_drop_the_others_sent_with_them() {
+        let mut app = combat_app();
+        let world = app.world_mut();
+        let me = world.spawn(TurnPlayer).id();
+        let enemy = world.spawn_empty().id();
+        let a = U16Vec2::new(0, 0);
+        let b = U16Vec2::new(1, 0);
+        let attacker = spawn_creature(world, me, a, b);
+        let defender = spawn_creature(world, enemy, b, a);
+
+        app.world_mut().write_message(AttackRequest {
+            attacker: defender,
+            target: attacker,
+        });
+        app.world_mut().write_message(AttackRequest {
+            attacker,
+            target: defender,
+        });
+        app.update();
+
+        assert_eq!(app.world().get::<CurrentDefense>(defender).unwrap().0, 3);
+        assert!(app.world().get::<AttackedThisTurn>(defender).is_none());
+    }
+
+    #[test]
+    fn weakened_attackers_deal_less_damage() {
+        let mut app = combat_app();
+        let world = app.world_mut();
+        let me = world.spawn(TurnPlayer).id();
+        let enemy = world.spawn_empty().id();
+        let a = U16Vec2::new(0, 0);
+        let b = U16Vec2::new(1, 0);
+        let attacker = spawn_creature(world, me, a, b);
+        let defender = spawn_creature(world, enemy, b, U16Vec2::new(5, 5));
+        let tile = world.get::<OnBoard>(attacker).unwrap().position;
+        let weakening = world
+            .resource_mut::<EffectRegistry>()
+            .insert(TileEffectDef {
+                name: EffectType::new("Weakening"),
+                affects: Affects::Enemy,
+                rule: EffectRule {
+                    attack_modifier: -1,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        world.spawn((weakening, Owner(enemy), ChildOf(tile)));
+
+        app.world_mut().write_message(AttackRequest {
+            attacker,
+            target: defender,
+        });
+        app.update();
+
+        assert_eq!(app.world().get::<CurrentDefense>(defender).unwrap().0, 4);
+    }
+
+    #[test]
+    fn defenders_whose_damage_was_prevented_strike_back() {
+        let mut app = combat_app();
+        let world = app.world_mut();
+        let me = world.spawn(TurnPlayer).id();
+        let enemy = world.spawn_empty().id();
+        let a = U16Vec2::new(0, 0);
+        let b = U16Vec2::new(1, 0);
+        let attacker = spawn_creature(world, me, a, b);
+        let defender = spawn_creature(world, enemy, b, a);
+        world.entity_mut(defender).insert(CurrentDefense(2));
+        world.spawn((
+            AbilityData(AbilityDef {
+                trigger: TriggerDef::Before {
+                    event: HookDef::Damaged,
+                    once_per_turn: false,
+                },
+                condition: ConditionDef::Always,
+                speed: Default::default(),
+                timing: Default::default(),
+                effects: vec![],
+                replacement: Some(ReplacementDef::Prevent),
+            }),
+            Action { caster: defender },
+        ));
+        app.update();
+
+        app.world_mut().write_message(AttackRequest {
+            attacker,
+            target: defender,
+        });
+        app.update();
+
+        assert_eq!(app.world().get::<CurrentDefense>(defender).unwrap().0, 2);
+        assert_eq!(app.world().get::<CurrentDefense>(attacker).unwrap().0, 3);
+    }
+}
@@ -1,14 +1,259 @@
-use std::str::FromStr;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
 
-use bevy::ecs::{bundle::Bundle, component::Component, entity::Entity};
+use bevy::{
+    ecs::{
+        bundle::Bundle,
+        component::Component,
+        entity::Entity,
+        hierarchy::Children,
+        query::{QueryEntityError, With},
+        resource::Resource,
+        system::{Query, Res, SystemParam},
+    },
+    math::U16Vec2,
+};
+use ron::value::RawValue;
 
-use crate::{board::tile::EffectsOnTile, player::Player};
-use serde::{Deserialize, Serialize};
+use crate::{
+    board::tile::{EffectsOnTile, Occupant, Position},
+    components::Owner,
+    def::tile_effect::TileEffectDef,
+    error::GameError,
+};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{EnumAccess, VariantAccess, Visitor},
+    ser::Error,
+};
 
-#[derive(Component, Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize)]
-pub enum EffectType {
-    Slow,
-    Weakening,
+/// The kind of a tile effect, named after its `*.effect.ron` file in
+/// `assets/effects`. In RON it is written as a bare name, e.g. `Burning`.
+/// Only effects declared in the [`EffectRegistry`] exist, so cards and saves
+/// naming any other effect fail to load.
+#[derive(Component, Clone, Default, PartialEq, PartialOrd, Ord, Eq, Hash)]
+pub struct EffectType(String);
+
+impl EffectType {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for EffectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for EffectType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Written as is, so the name comes out bare like a unit variant.
+        RawValue::from_ron(&self.0)
+            .map_err(|_| S::Error::custom(format!("`{}` is not a tile effect name", self.0)))?
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for EffectType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_enum("EffectType", &[], NameVisitor)
+            .map(Self)
+    }
+}
+
+struct NameVisitor;
+
+impl<'de> Visitor<'de> for NameVisitor {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the name of a tile effect")
+    }
+
+    fn visit_str<E>(self, name: &str) -> Result<String, E> {
+        Ok(name.to_owned())
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<String, A::Error> {
+        let (name, variant): (Name, _) = data.variant()?;
+        variant.unit_variant()?;
+        Ok(name.0)
+    }
+}
+
+/// A bare effect name, read as an identifier.
+struct Name(String);
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(NameVisitor).map(Name)
+    }
+}
+
+/// What an effect does to the tile it lies on. Several effects on one tile
+/// add up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EffectRule {
+    /// Added to the attack of creatures standing on the tile.
+    #[serde(default)]
+    pub attack_modifier: i16,
+    /// Subtracted from the combat damage creatures standing on the tile
+    /// take; negative values make them take more.
+    #[serde(default)]
+    pub defense_modifier: i16,
+    /// Extra movement points needed to move off the tile.
+    #[serde(default)]
+    pub movement_cost: u16,
+    /// Creatures cannot move onto or through the tile.
+    #[serde(default)]
+    pub blocks_movement: bool,
+}
+
+impl EffectRule {
+    fn combine(self, other: Self) -> Self {
+        Self {
+            attack_modifier: self.attack_modifier.saturating_add(other.attack_modifier),
+            defense_modifier: self.defense_modifier.saturating_add(other.defense_modifier),
+            movement_cost: self.movement_cost.saturating_add(other.movement_cost),
+            blocks_movement: self.blocks_movement || other.blocks_movement,
+        }
+    }
+
+    /// `attack` with the attack modifier applied.
+    pub fn modify_attack(&self, attack: u16) -> u16 {
+        attack.saturating_add_signed(self.attack_modifier)
+    }
+
+    /// The combat `damage` a creature on the tile actually takes.
+    pub fn modify_damage_taken(&self, damage: u16) -> u16 {
+        damage.saturating_add_signed(self.defense_modifier.saturating_neg())
+    }
+}
+
+/// Which creatures an effect applies to, seen from the effect's owner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Affects {
+    #[default]
+    All,
+    Friendly,
+    Enemy,
+}
+
+impl Affects {
+    /// Whether the effect applies to creatures of `player`.
+    pub fn includes(self, effect_owner: Entity, player: Entity) -> bool {
+        match self {
+            Affects::All => true,
+            Affects::Friendly => effect_owner == player,
+            Affects::Enemy => effect_owner != player,
+        }
+    }
+}
+
+/// Whose turns an effect's duration counts down on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EffectTicks {
+    /// Every turn start.
+    #[default]
+    EveryTurn,
+    /// Only at the start of the effect owner's turns.
+    OwnersTurn,
+}
+
+/// Every tile effect declared in `assets/effects`, by name.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct EffectRegistry(HashMap<EffectType, TileEffectDef>);
+
+impl EffectRegistry {
+    /// Declares the effect type of `def`.
+    pub fn insert(&mut self, def: TileEffectDef) -> EffectType {
+        let effect = def.name.clone();
+        self.0.insert(effect.clone(), def);
+        effect
+    }
+
+    pub fn get(&self, effect: &EffectType) -> Option<&TileEffectDef> {
+        self.0.get(effect)
+    }
+
+    /// Replaces the movement cost of every effect named in `costs`.
+    pub fn set_movement_costs(&mut self, costs: &BTreeMap<EffectType, u16>) {
+        for (effect, cost) in costs {
+            if let Some(def) = self.0.get_mut(effect) {
+                def.rule.movement_cost = *cost;
+            }
+        }
+    }
+
+    /// Fails on the first of `effects` that was never declared.
+    pub fn check<'a>(
+        &self,
+        effects: impl IntoIterator<Item = &'a EffectType>,
+    ) -> Result<(), GameError> {
+        match effects
+            .into_iter()
+            .find(|effect| !self.0.contains_key(effect))
+        {
+            Some(unknown) => Err(GameError::NotFound(format!("tile effect `{unknown:?}`"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether an `effect` owned by `effect_owner` applies to creatures of
+    /// `player`.
+    pub fn affects(&self, effect: &EffectType, effect_owner: Entity, player: Entity) -> bool {
+        self.get(effect)
+            .is_some_and(|def| def.affects.includes(effect_owner, player))
+    }
+
+    /// The rule an `effect` owned by `effect_owner` has for creatures of
+    /// `player`.
+    pub fn rule(&self, effect: &EffectType, effect_owner: Entity, player: Entity) -> EffectRule {
+        match self.get(effect) {
+            Some(def) if def.affects.includes(effect_owner, player) => def.rule,
+            _ => EffectRule::default(),
+        }
+    }
+}
+
+/// Looks up tile positions, their occupants and the rule the effects on a
+/// tile add up to.
+#[derive(SystemParam)]
+pub struct Tiles<'w, 's> {
+    positions: Query<'w, 's, &'static Position>,
+    occupants: Query<'w, 's, (), With<Occupant>>,
+    children: Query<'w, 's, &'static Children>,
+    effects: Query<'w, 's, (&'static EffectType, &'static Owner)>,
+    registry: Res<'w, EffectRegistry>,
+}
+
+impl Tiles<'_, '_> {
+    pub fn position(&self, tile: Entity) -> Result<U16Vec2, QueryEntityError> {
+        self.positions.get(tile).map(|position| position.0)
+    }
+
+    pub fn is_occupied(&self, tile: Entity) -> bool {
+        self.occupants.contains(tile)
+    }
+
+    /// The rule the effects on `tile` add up to for creatures of `player`.
+    pub fn rule(&self, tile: Entity, player: Entity) -> EffectRule {
+        self.children
+            .get(tile)
+            .into_iter()
+            .flat_map(|children| children.iter())
+            .filter_map(|&child| self.effects.get(child).ok())
+            .map(|(effect, owner)| self.registry.rule(effect, owner.0, player))
+            .fold(EffectRule::default(), EffectRule::combine)
+    }
 }
 
 #[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
@@ -23,11 +268,11 @@ impl EffectDuration {
     }
 }
 
-#[derive(Bundle, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Bundle, Debug, Clone, PartialEq, Eq)]
 pub struct Effect {
     pub effect_type: EffectType,
     duration: EffectDuration,
-    pub owner: Player,
+    pub owner: Owner,
 }
 
 #[derive(Component, Debug)]
@@ -38,16 +283,16 @@ pub struct EffectTile {
 }
 
 impl Effect {
-    pub fn new(effect_type: EffectType, duration: u16, owner: Player) -> Self {
+    pub fn new(effect_type: EffectType, duration: u16, owner: Entity) -> Self {
         Self {
             effect_type,
             duration: EffectDuration(duration),
-            owner,
+            owner: Owner(owner),
         }
     }
 
-    pub fn effect_type(&self) -> EffectType {
-        self.effect_type
+    pub fn effect_type(&self) -> &EffectType {
+        &self.effect_type
     }
 
     pub fn duration(&self) -> u16 {
@@ -58,17 +303,3 @@ impl Effect {
         self.duration.0 = self.duration.0.saturating_sub(1);
     }
 }
-
-impl FromStr for EffectType {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "slow" => Ok(Self::Slow),
-            "weakening" => Ok(Self::Weakening),
-            _ => Err(()),
-        }
-    }
-}
-
-
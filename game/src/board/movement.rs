@@ -1,20 +1,33 @@
 use std::fmt::Display;
 
-use crate::{Result, card::creature::BaseMovementPoints, components::Owner, player::TurnPlayer};
+use crate::{
+    Result,
+    card::{abilities::CardAbilities, creature::BaseMovementPoints},
+    components::Owner,
+    player::TurnPlayer,
+};
 use bevy::{
     ecs::{
         entity::Entity,
-        hierarchy::ChildOf,
         message::{Message, MessageReader, MessageWriter},
         query::With,
         system::{Commands, Query, Res},
     },
-    math::U16Vec2,
+    log::warn,
+    math::{I16Vec2, U16Vec2},
 };
 
 use crate::{
-    board::{BoardRes, effect::EffectType, place_error::BoardError, tile::Occupant},
-    card::{CreatureCard, CurrentMovementPoints, OnBoard, creature::MovementPattern},
+    board::{
+        BoardRes,
+        effect::{EffectRule, Tiles},
+        place_error::BoardError,
+    },
+    card::{
+        CreatureCard, CurrentMovementPoints, OnBoard,
+        creature::MovementPattern,
+        modifiers::{Stat, StatModifiers},
+    },
     events::CardMoved,
 };
 
@@ -30,10 +43,10 @@ fn check_valid_move_and_get_cost(
     to: U16Vec2,
     movement_points: u16,
     movement_pattern: &MovementPattern,
-    tile_has_slow: bool,
+    extra_cost: u16,
 ) -> Result<u16, MoveValidationError> {
     // Check movement points requirement
-    let required_points = if tile_has_slow { 2 } else { 1 };
+    let required_points = 1 + extra_cost;
 
     if movement_points < required_points {
         return Err(MoveValidationError::InsufficientMovementPoints {
@@ -58,27 +71,72 @@ pub fn is_validate_move() -> bool {
     true
 }
 
+/// The tiles strictly between `from` and `from + delta` for straight and
+/// diagonal moves. Other moves, like a knight's, have no path to block.
+fn path_between(from: U16Vec2, delta: I16Vec2) -> Vec<U16Vec2> {
+    let (dx, dy) = (delta.x.abs(), delta.y.abs());
+    if dx != 0 && dy != 0 && dx != dy {
+        return Vec::new();
+    }
+    let step = delta.signum();
+    let from = from.as_i16vec2();
+    (1..dx.max(dy))
+        .map(|i| (from + step * i).as_u16vec2())
+        .collect()
+}
+
+/// Whether a creature with `abilities` may move from `from` by `delta` past
+/// the tiles in between. Only Flying, Jumping or Digging creatures get past
+/// occupied tiles, but no creature gets through a tile blocked by an effect.
+fn path_is_clear(
+    from: U16Vec2,
+    delta: I16Vec2,
+    abilities: &CardAbilities,
+    is_occupied: impl Fn(U16Vec2) -> bool,
+    is_walled: impl Fn(U16Vec2) -> bool,
+) -> bool {
+    path_between(from, delta)
+        .into_iter()
+        .all(|tile| !is_walled(tile) && (abilities.passes_occupants() || !is_occupied(tile)))
+}
+
+/// Extra movement points a creature with `abilities` needs to leave a tile
+/// with `rule`.
+fn extra_cost(rule: &EffectRule, abilities: &CardAbilities) -> u16 {
+    if abilities.ignores_movement_cost() {
+        0
+    } else {
+        rule.movement_cost
+    }
+}
+
 /// Computes the set of tiles a creature standing on `from` can reach in a single
 /// move, matching the validation performed by `handle_movement` (bounds,
-/// movement pattern, remaining movement points, and occupancy).
+/// movement pattern, remaining movement points, occupancy, the effect rules
+/// of the tiles as given by `tile_rule` and the creature's keyword abilities).
 pub fn reachable_tiles(
     board: &BoardRes,
     from: U16Vec2,
     movement_points: u16,
     pattern: &MovementPattern,
     occupied: &[U16Vec2],
-    current_tile_has_slow: bool,
+    tile_rule: impl Fn(U16Vec2) -> EffectRule,
+    abilities: &CardAbilities,
 ) -> Vec<U16Vec2> {
-    let required_points = if current_tile_has_slow { 2 } else { 1 };
+    let required_points = 1 + extra_cost(&tile_rule(from), abilities);
     if movement_points < required_points {
         return Vec::new();
     }
+    let is_occupied = |tile: U16Vec2| occupied.contains(&tile);
+    let is_walled = |tile: U16Vec2| tile_rule(tile).blocks_movement;
     let mut reachable = Vec::new();
     for delta in &pattern.0 {
-        if let Some(target) = board.add_relative_tile(from, *delta) {
-            if !occupied.contains(&target) {
-                reachable.push(target);
-            }
+        if let Some(target) = board.add_relative_tile(from, *delta)
+            && !is_occupied(target)
+            && !is_walled(target)
+            && path_is_clear(from, *delta, abilities, is_occupied, is_walled)
+        {
+            reachable.push(target);
         }
     }
     reachable
@@ -89,6 +147,9 @@ pub enum MoveValidationError {
     InsufficientMovementPoints { required: u16, available: u16 },
     InvalidMovePattern { from: U16Vec2, to: U16Vec2 },
     Occupied,
+    Blocked,
+    PathBlocked,
+    NotTurnPlayer,
 }
 
 impl Display for MoveValidationError {
@@ -106,6 +167,11 @@ impl Display for MoveValidationError {
                 write!(f, "InvalidMovePattern from {}, to {}", from, to)
             }
             MoveValidationError::Occupied => write!(f, "Tile is occupied"),
+            MoveValidationError::Blocked => write!(f, "Tile is blocked by an effect"),
+            MoveValidationError::PathBlocked => write!(f, "Path is blocked"),
+            MoveValidationError::NotTurnPlayer => {
+                write!(f, "Only the turn player can move their creatures")
+            }
         }
     }
 }
@@ -116,30 +182,62 @@ pub fn handle_movement(
     mut commands: Commands,
     mut move_requests: MessageReader<MoveRequest>,
     mut move_completed: MessageWriter<CardMoved>,
-    mut creatures: Query<(&mut CurrentMovementPoints, &MovementPattern), With<CreatureCard>>,
+    mut creatures: Query<
+        (
+            &mut CurrentMovementPoints,
+            &MovementPattern,
+            &Owner,
+            Option<&CardAbilities>,
+        ),
+        With<CreatureCard>,
+    >,
     board: Res<BoardRes>,
-    occupied: Query<&Occupant>,
-    effects: Query<(&EffectType, &ChildOf)>,
+    tiles: Tiles,
+    turn_player: Query<Entity, With<TurnPlayer>>,
 ) -> Result {
     for event in move_requests.read() {
-        let (mut movement, pattern) = creatures
+        let (mut movement, pattern, owner, abilities) = creatures
             .get_mut(event.entity)
             .map_err(|_| BoardError::CardNotFound)?;
+        if turn_player.single().ok() != Some(owner.0) {
+            warn!(
+                "Skipping move of {}: {}",
+                event.entity,
+                MoveValidationError::NotTurnPlayer
+            );
+            continue;
+        }
         let old_pos = event.from;
 
         let old_tile = board.get_tile(&old_pos).ok_or(BoardError::TileNotFound)?;
         let new_tile = board.get_tile(&event.to).ok_or(BoardError::TileNotFound)?;
 
-        if occupied.contains(new_tile) {
+        if tiles.is_occupied(new_tile) {
             return Err(BoardError::InvalidMove(MoveValidationError::Occupied).into());
         }
+        if tiles.rule(new_tile, owner.0).blocks_movement {
+            return Err(BoardError::InvalidMove(MoveValidationError::Blocked).into());
+        }
 
-        let tile_has_slow = effects
-            .iter()
-            .any(|(ef, co)| co.0 == old_tile && *ef == EffectType::Slow);
+        let abilities = abilities.cloned().unwrap_or_default();
+        let delta = event.to.as_i16vec2() - old_pos.as_i16vec2();
+        let is_occupied = |pos: U16Vec2| {
+            board
+                .get_tile(&pos)
+                .is_some_and(|tile| tiles.is_occupied(tile))
+        };
+        let is_walled = |pos: U16Vec2| {
+            board
+                .get_tile(&pos)
+                .is_some_and(|tile| tiles.rule(tile, owner.0).blocks_movement)
+        };
+        if !path_is_clear(old_pos, delta, &abilities, is_occupied, is_walled) {
+            return Err(BoardError::InvalidMove(MoveValidationError::PathBlocked).into());
+        }
 
+        let extra_cost = extra_cost(&tiles.rule(old_tile, owner.0), &abilities);
         let cost =
-            check_valid_move_and_get_cost(old_pos, event.to, movement.0, pattern, tile_has_slow)
+            check_valid_move_and_get_cost(old_pos, event.to, movement.0, pattern, extra_cost)
                 .map_err(BoardError::InvalidMove)?;
 
         commands
@@ -157,12 +255,20 @@ pub fn handle_movement(
 }
 
 pub(crate) fn refresh_movement_points(
-    creatures: Query<(&mut CurrentMovementPoints, &Owner, &BaseMovementPoints), With<CreatureCard>>,
+    creatures: Query<
+        (
+            &mut CurrentMovementPoints,
+            &Owner,
+            &BaseMovementPoints,
+            &StatModifiers,
+        ),
+        With<CreatureCard>,
+    >,
     player: Query<Entity, With<TurnPlayer>>,
 ) {
-    for (mut movement, owner, base_movement) in creatures.into_iter() {
+    for (mut movement, owner, base_movement, modifiers) in creatures.into_iter() {
         if owner.0 == player.single().expect("No turn player found") {
-            movement.0 = base_movement.0
+            movement.0 = modifiers.apply(Stat::Speed, base_movement.0)
         }
     }
 }
@@ -170,7 +276,12 @@ pub(crate) fn refresh_movement_points(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::BoardRes;
+    use crate::{
+        board::BoardRes,
+        card::abilities::Abilities,
+        replay::{settle, tests::game_with_creature_in_play},
+        turn_controller::EndTurnPressed,
+    };
     use bevy::math::{I16Vec2, U16Vec2};
     use std::collections::HashMap;
 
@@ -182,6 +293,17 @@ mod tests {
         }
     }
 
+    fn plain(_: U16Vec2) -> EffectRule {
+        EffectRule::default()
+    }
+
+    fn slow(_: U16Vec2) -> EffectRule {
+        EffectRule {
+            movement_cost: 1,
+            ..Default::default()
+        }
+    }
+
     fn orthogonal_pattern() -> MovementPattern {
         MovementPattern(vec![
             I16Vec2::new(0, 1),
@@ -195,7 +317,8 @@ mod tests {
     fn bounds_are_clipped_at_corner() {
         let board = test_board();
         let pattern = orthogonal_pattern();
-        let reachable = reachable_tiles(&board, U16Vec2::new(0, 0), 5, &pattern, &[], false);
+        let none = CardAbilities::default();
+        let reachable = reachable_tiles(&board, U16Vec2::new(0, 0), 5, &pattern, &[], plain, &none);
         assert_eq!(reachable.len(), 2);
         assert!(reachable.contains(&U16Vec2::new(1, 0)));
         assert!(reachable.contains(&U16Vec2::new(0, 1)));
@@ -205,7 +328,8 @@ mod tests {
     fn insufficient_movement_points_returns_empty() {
         let board = test_board();
         let pattern = orthogonal_pattern();
-        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 0, &pattern, &[], false);
+        let none = CardAbilities::default();
+        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 0, &pattern, &[], plain, &none);
         assert!(reachable.is_empty());
     }
 
@@ -213,14 +337,15 @@ mod tests {
     fn slow_tile_doubles_required_points() {
         let board = test_board();
         let pattern = orthogonal_pattern();
+        let none = CardAbilities::default();
         // With 1 point left, a slow tile costs 2 so nothing is reachable.
-        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 1, &pattern, &[], true);
+        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 1, &pattern, &[], slow, &none);
         assert!(reachable.is_empty());
         // With 2 points left, all four orthogonal neighbors become reachable.
-        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 2, &pattern, &[], true);
+        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 2, &pattern, &[], slow, &none);
         assert_eq!(reachable.len(), 4);
         // Without slow, 1 point is enough.
-        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 1, &pattern, &[], false);
+        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 1, &pattern, &[], plain, &none);
         assert_eq!(reachable.len(), 4);
     }
 
@@ -228,10 +353,94 @@ mod tests {
     fn occupied_tiles_are_filtered_out() {
         let board = test_board();
         let pattern = orthogonal_pattern();
+        let none = CardAbilities::default();
         let occupied = vec![U16Vec2::new(3, 2), U16Vec2::new(2, 3)];
-        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 5, &pattern, &occupied, false);
+        let reachable = reachable_tiles(
+            &board,
+            U16Vec2::new(2, 2),
+            5,
+            &pattern,
+            &occupied,
+            plain,
+            &none,
+        );
         assert_eq!(reachable.len(), 2);
         assert!(reachable.contains(&U16Vec2::new(1, 2)));
         assert!(reachable.contains(&U16Vec2::new(2, 1)));
     }
+
+    #[test]
+    fn occupants_on_the_path_block_only_walking_creatures() {
+        let board = test_board();
+        let pattern = MovementPattern(vec![I16Vec2::new(2, 0)]);
+        let occupied = vec![U16Vec2::new(1, 0)];
+        let reach = |abilities: Vec<Abilities>| {
+            reachable_tiles(
+                &board,
+                U16Vec2::new(0, 0),
+                1,
+                &pattern,
+                &occupied,
+                plain,
+                &CardAbilities(abilities),
+            )
+        };
+        assert!(reach(vec![]).is_empty());
+        for ability in [Abilities::Flying, Abilities::Jumping, Abilities::Digging] {
+            assert_eq!(reach(vec![ability]), vec![U16Vec2::new(2, 0)]);
+        }
+    }
+
+    #[test]
+    fn flying_ignores_slow_tiles() {
+        let board = test_board();
+        let pattern = orthogonal_pattern();
+        let flying = CardAbilities(vec![Abilities::Flying]);
+        let reachable =
+            reachable_tiles(&board, U16Vec2::new(2, 2), 1, &pattern, &[], slow, &flying);
+        assert_eq!(reachable.len(), 4);
+    }
+
+    #[test]
+    fn blocking_effects_keep_creatures_off_the_tile() {
+        let board = test_board();
+        let pattern = orthogonal_pattern();
+        let none = CardAbilities::default();
+        let wall = |tile: U16Vec2| EffectRule {
+            blocks_movement: tile == U16Vec2::new(3, 2),
+            ..Default::default()
+        };
+        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 1, &pattern, &[], wall, &none);
+        assert_eq!(reachable.len(), 3);
+        assert!(!reachable.contains(&U16Vec2::new(3, 2)));
+
+        // Not even flying creatures get across the wall.
+        let across = MovementPattern(vec![I16Vec2::new(2, 0)]);
+        let flying = CardAbilities(vec![Abilities::Flying]);
+        let reachable = reachable_tiles(&board, U16Vec2::new(2, 2), 1, &across, &[], wall, &flying);
+        assert!(reachable.is_empty());
+    }
+
+    #[test]
+    fn creatures_cannot_be_moved_by_the_opponent() {
+        let (mut app, creature) = game_with_creature_in_play(11);
+        app.world_mut().write_message(EndTurnPressed);
+        settle(&mut app);
+        let world = app.world_mut();
+        let owner = world.get::<Owner>(creature).unwrap().0;
+        assert!(world.get::<TurnPlayer>(owner).is_none());
+        world.get_mut::<CurrentMovementPoints>(creature).unwrap().0 = 2;
+        let from_tile = world.get::<OnBoard>(creature).unwrap().position;
+
+        world.write_message(MoveRequest {
+            entity: creature,
+            from: U16Vec2::new(3, 6),
+            to: U16Vec2::new(4, 6),
+        });
+        settle(&mut app);
+
+        let world = app.world();
+        assert_eq!(world.get::<OnBoard>(creature).unwrap().position, from_tile);
+        assert_eq!(world.get::<CurrentMovementPoints>(creature).unwrap().0, 2);
+    }
 }
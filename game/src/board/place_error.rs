@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 use bevy::math::I16Vec2;
 
-use super::movement::MoveValidationError;
+use super::{combat::AttackValidationError, movement::MoveValidationError};
 
 #[derive(Debug)]
 pub enum BoardError {
@@ -13,6 +13,7 @@ pub enum BoardError {
     NoMovementPoints,
     CardNotFound,
     InvalidMove(MoveValidationError),
+    InvalidAttack(AttackValidationError),
 }
 
 impl Display for BoardError {
@@ -27,6 +28,9 @@ impl Display for BoardError {
             BoardError::InvalidMove(move_validation_error) => {
                 write!(f, "InvalidMove {}", move_validation_error)
             }
+            BoardError::InvalidAttack(attack_validation_error) => {
+                write!(f, "InvalidAttack {}", attack_validation_error)
+            }
         }
     }
 }
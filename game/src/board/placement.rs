@@ -1,15 +1,19 @@
-use bevy::ecs::prelude::*;
+use bevy::ecs::{prelude::*, system::SystemParam};
 
 use crate::{
+    actions::{spell_speed::SpellSpeed, stack::PlayWindow},
     board::{
         BoardRes,
         place_error::BoardError,
-        tile::{Occupant, Tile},
+        tile::{Occupant, SetTrap, Tile},
+    },
+    card::{
+        Cost, CreatureCard, FaceDown, InGraveyard, InHand, OnBoard, SetOnTile, SpellCard, TrapCard,
     },
-    card::{Cost, CreatureCard, InHand, OnBoard},
     components::Owner,
     error::GameError,
-    player::PlayerResources,
+    events::{SpellPlayed, TrapPlaced},
+    player::{Player, PlayerResources},
     turn_controller::CardPlayRequested,
 };
 
@@ -18,8 +22,16 @@ pub struct CardPlayed {
     pub card: Entity,
 }
 
+/// Incoming play requests together with the rules on when cards may be
+/// played.
+#[derive(SystemParam)]
+pub struct PlayRequests<'w, 's> {
+    requests: MessageReader<'w, 's, CardPlayRequested>,
+    window: PlayWindow<'w, 's>,
+}
+
 pub fn place_card(
-    mut card_place_requests: MessageReader<CardPlayRequested>,
+    mut plays: PlayRequests,
     mut card_placed: MessageWriter<CardPlayed>,
     free_tiles: Query<&Tile, Without<Occupant>>,
     cards: Query<(&Cost, &Owner), With<CreatureCard>>,
@@ -27,7 +39,13 @@ pub fn place_card(
     board: Res<BoardRes>,
     mut commands: Commands,
 ) -> Result {
-    for card_place_request in card_place_requests.read() {
+    for card_place_request in plays.requests.read() {
+        // Spells and traps have their own play paths.
+        let Ok((cost, owner)) = cards.get(card_place_request.card) else {
+            continue;
+        };
+        plays.window.check(owner.0, SpellSpeed::Slow)?;
+
         let tile = board
             .get_tile(&card_place_request.position)
             .ok_or(BoardError::TileNotFound)?;
@@ -35,8 +53,6 @@ pub fn place_card(
             return Err(BoardError::TileOccupied.into());
         }
 
-        let (cost, owner) = cards.get(card_place_request.card)?;
-
         let mut player = players.get_mut(owner.0)?;
 
         if player.gold < cost.value {
@@ -55,3 +71,141 @@ pub fn place_card(
     }
     Ok(())
 }
+
+/// Sets trap cards face-down on an empty tile without another trap.
+pub fn set_trap(
+    mut plays: PlayRequests,
+    mut trap_placed: MessageWriter<TrapPlaced>,
+    traps: Query<(&Cost, &Owner, &InHand), With<TrapCard>>,
+    free_tiles: Query<&Tile, (Without<Occupant>, Without<SetTrap>)>,
+    mut players: Query<(&mut PlayerResources, &Player)>,
+    board: Res<BoardRes>,
+    mut commands: Commands,
+) -> Result {
+    for request in plays.requests.read() {
+        let Ok((cost, owner, _)) = traps.get(request.card) else {
+            continue;
+        };
+        plays.window.check(owner.0, SpellSpeed::Slow)?;
+
+        let tile = board
+            .get_tile(&request.position)
+            .ok_or(BoardError::TileNotFound)?;
+        if !free_tiles.contains(tile) {
+            return Err(BoardError::TileOccupied.into());
+        }
+
+        let (mut resources, &player) = players.get_mut(owner.0)?;
+
+        if resources.gold < cost.value {
+            return Err(GameError::InsufficientGold.into());
+        }
+
+        resources.gold -= cost.value;
+
+        commands
+            .entity(request.card)
+            .remove::<InHand>()
+            .insert((SetOnTile { tile }, FaceDown));
+        trap_placed.write(TrapPlaced {
+            owner: player,
+            card: request.card,
+            position: request.position,
+        });
+    }
+    Ok(())
+}
+
+/// Casts spell cards: the owner pays the cost, the card goes to the
+/// graveyard and its `OnPlay` abilities are started through `CardPlayed`.
+/// While abilities wait on the stack only Fast and Instant spells of the
+/// priority holder may be cast, in response.
+pub fn cast_spell(
+    mut plays: PlayRequests,
+    mut card_played: MessageWriter<CardPlayed>,
+    mut spell_played: MessageWriter<SpellPlayed>,
+    spells: Query<(&Cost, &Owner, &InHand), With<SpellCard>>,
+    mut players: Query<(&mut PlayerResources, &Player)>,
+    mut commands: Commands,
+) -> Result {
+    for request in plays.requests.read() {
+        let Ok((cost, owner, _)) = spells.get(request.card) else {
+            continue;
+        };
+        let speed = plays.window.card_speed(request.card);
+        plays.window.check(owner.0, speed)?;
+
+        let (mut resources, &player) = players.get_mut(owner.0)?;
+
+        if resources.gold < cost.value {
+            return Err(GameError::InsufficientGold.into());
+        }
+
+        resources.gold -= cost.value;
+
+        commands
+            .entity(request.card)
+            .remove::<InHand>()
+            .insert(InGraveyard { owner: owner.0 });
+        card_played.write(CardPlayed { card: request.card });
+        spell_played.write(SpellPlayed {
+            owner: player,
+            card: request.card,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::stack::{EffectStack, Priority},
+        events::GameMessagesPlugin,
+        player::{PlayerBundle, TurnPlayer},
+    };
+    use bevy::{
+        app::{App, Update},
+        math::U16Vec2,
+    };
+
+    #[test]
+    fn casting_a_spell_pays_gold_and_moves_it_to_the_graveyard() {
+        let mut app = App::new();
+        app.add_plugins(GameMessagesPlugin)
+            .add_message::<CardPlayRequested>()
+            .add_message::<CardPlayed>()
+            .init_resource::<EffectStack>()
+            .init_resource::<Priority>()
+            .add_systems(Update, cast_spell);
+
+        let player = app
+            .world_mut()
+            .spawn((Player { number: 0 }, PlayerBundle::default(), TurnPlayer))
+            .id();
+        let spell = app
+            .world_mut()
+            .spawn((
+                SpellCard,
+                Cost { value: 3 },
+                Owner(player),
+                InHand { parent: player },
+            ))
+            .id();
+
+        app.world_mut().write_message(CardPlayRequested {
+            card: spell,
+            hand_position: 0,
+            position: U16Vec2::ZERO,
+        });
+        app.update();
+
+        let resources = app.world().get::<PlayerResources>(player).unwrap();
+        assert_eq!(resources.gold, 7);
+        assert!(app.world().get::<InHand>(spell).is_none());
+        assert_eq!(app.world().get::<InGraveyard>(spell).unwrap().owner, player);
+
+        let played = app.world().resource::<Messages<SpellPlayed>>();
+        assert_eq!(played.len(), 1);
+    }
+}
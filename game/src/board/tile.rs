@@ -3,7 +3,10 @@ use bevy::{
     math::U16Vec2,
 };
 
-use crate::{board::effect::EffectTile, card::OnBoard};
+use crate::{
+    board::effect::EffectTile,
+    card::{OnBoard, SetOnTile},
+};
 
 #[derive(Bundle, Default)]
 pub struct TileBundel {
@@ -20,6 +23,17 @@ pub struct Position(pub U16Vec2);
 #[relationship_target(relationship = OnBoard)]
 pub struct Occupant(Entity);
 
+/// The face-down trap set on this tile, if any.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+#[relationship_target(relationship = SetOnTile)]
+pub struct SetTrap(Entity);
+
+impl SetTrap {
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
 #[derive(Component, Clone, Debug, PartialEq, Eq)]
 #[relationship_target(relationship = EffectTile)]
 pub struct EffectsOnTile(Vec<Entity>);
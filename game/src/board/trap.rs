@@ -0,0 +1,119 @@
+//! Reveal rules for face-down traps set on tiles.
+
+use bevy::prelude::*;
+
+use crate::{
+    GameRng,
+    actions::{conditions::Condition, targeting::filters::FilterParams},
+    board::tile::SetTrap,
+    card::{CreatureCard, FaceDown, InGraveyard, OnBoard, SetOnTile, trap_card::RevealCondition},
+    components::Owner,
+    events::TrapRevealed,
+};
+
+/// Reveals the trap on a tile when an enemy creature enters it.
+pub fn reveal_on_enter(
+    event: On<Insert, OnBoard>,
+    creatures: Query<(&OnBoard, &Owner), With<CreatureCard>>,
+    tiles: Query<&SetTrap>,
+    traps: Query<&Owner, With<FaceDown>>,
+    mut commands: Commands,
+    mut revealed: MessageWriter<TrapRevealed>,
+) {
+    let Ok((on_board, owner)) = creatures.get(event.entity) else {
+        return;
+    };
+    let Ok(set_trap) = tiles.get(on_board.position) else {
+        return;
+    };
+    let trap = set_trap.get();
+    let Ok(trap_owner) = traps.get(trap) else {
+        return;
+    };
+    if trap_owner == owner {
+        return;
+    }
+
+    info!("Creature {} sprang trap {}", event.entity, trap);
+    turn_over(&mut commands, trap, trap_owner.0);
+    revealed.write(TrapRevealed {
+        trap,
+        intruder: Some(event.entity),
+    });
+}
+
+/// Reveals face-down traps whose own reveal condition holds.
+pub fn reveal_on_condition(
+    traps: Query<(Entity, &RevealCondition, &Owner), With<FaceDown>>,
+    filter_params: FilterParams,
+    mut rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut revealed: MessageWriter<TrapRevealed>,
+) {
+    let mut value_params = filter_params.as_value_params(&mut rng);
+    for (trap, reveal_condition, owner) in &traps {
+        let condition = match Condition::try_from(&reveal_condition.0) {
+            Ok(condition) => condition,
+            Err(e) => {
+                warn!("Invalid reveal condition on trap {}: {}", trap, e);
+                continue;
+            }
+        };
+        if !condition.eval(&mut value_params, trap) {
+            continue;
+        }
+
+        info!("Reveal condition of trap {} met", trap);
+        turn_over(&mut commands, trap, owner.0);
+        revealed.write(TrapRevealed {
+            trap,
+            intruder: None,
+        });
+    }
+}
+
+/// A revealed trap is spent: it leaves its tile for the owner's graveyard.
+/// Its `OnReveal` abilities still resolve with the trap as caster.
+fn turn_over(commands: &mut Commands, trap: Entity, owner: Entity) {
+    commands
+        .entity(trap)
+        .remove::<(FaceDown, SetOnTile)>()
+        .insert(InGraveyard { owner });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::tile::Tile, events::GameMessagesPlugin};
+
+    #[test]
+    fn enemy_creature_entering_the_tile_springs_the_trap() {
+        let mut app = App::new();
+        app.add_plugins(GameMessagesPlugin)
+            .add_observer(reveal_on_enter);
+
+        let setter = app.world_mut().spawn_empty().id();
+        let intruder_owner = app.world_mut().spawn_empty().id();
+        let tile = app.world_mut().spawn(Tile).id();
+        let trap = app
+            .world_mut()
+            .spawn((Owner(setter), FaceDown, SetOnTile { tile }))
+            .id();
+        let intruder = app
+            .world_mut()
+            .spawn((CreatureCard, Owner(intruder_owner)))
+            .id();
+
+        app.world_mut()
+            .entity_mut(intruder)
+            .insert(OnBoard { position: tile });
+        app.world_mut().flush();
+
+        assert!(app.world().get::<FaceDown>(trap).is_none());
+        assert!(app.world().get::<SetTrap>(tile).is_none());
+        assert_eq!(app.world().get::<InGraveyard>(trap).unwrap().owner, setter);
+
+        let revealed = app.world().resource::<Messages<TrapRevealed>>();
+        assert_eq!(revealed.len(), 1);
+    }
+}
@@ -1,26 +1,30 @@
 use bevy::{
     ecs::{
+        bundle::Bundle,
         component::Component,
         entity::Entity,
+        error::Result,
         hierarchy::ChildOf,
-        query::With,
-        system::{Commands, Query, Res},
+        system::{Commands, Query, Res, ResMut},
     },
 };
 use derive_more::From;
 
 use crate::{
+    GameRng,
     actions::{AbilityData, Action},
-    board::tile::Occupant,
+    board::tile::{Occupant, SetTrap},
     card::{
         card_id::CardID,
         card_registry::CardRegistry,
         creature::{CreatureBundle},
-        deck_builder::DeckBuilder,
+        deck_builder::{DeckBuilder, DeckRegistry, PlayerDecks},
+        in_play_id::InPlayID,
         spell_card::{SpellBundle},
         trap_card::{TrapBundle},
     },
     components::Owner,
+    error::GameError,
     player::{Deck, Hand, Player},
 };
 
@@ -31,6 +35,7 @@ pub mod card_type;
 pub mod creature;
 pub mod deck_builder;
 pub mod in_play_id;
+pub mod modifiers;
 pub mod spell_card;
 pub mod trap_card;
 
@@ -68,6 +73,14 @@ pub struct OnBoard {
     pub position: Entity,
 }
 
+/// A trap set face-down on a tile.
+#[derive(Component, Debug, Clone)]
+#[relationship(relationship_target = SetTrap)]
+pub struct SetOnTile {
+    #[relationship]
+    pub tile: Entity,
+}
+
 #[derive(Component, Debug, Clone)]
 #[relationship(relationship_target = crate::player::Graveyard)]
 pub struct InGraveyard {
@@ -78,6 +91,10 @@ pub struct InGraveyard {
 #[derive(Component, Default)]
 pub struct Selected;
 
+/// Marks a set trap that has not been revealed yet.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct FaceDown;
+
 // ============================================
 // MUTABLE INSTANCE STATE (what changes during play)
 // ============================================
@@ -121,6 +138,15 @@ impl CardBundle {
             CardBundle::Trap { bundle } => bundle.card_id,
         }
     }
+
+    /// Spawns the card together with `extra`, e.g. its owner and location.
+    pub fn spawn(self, commands: &mut Commands, extra: impl Bundle) -> Entity {
+        match self {
+            CardBundle::Creature { bundle } => commands.spawn((bundle, extra)).id(),
+            CardBundle::Spell { bundle } => commands.spawn((bundle, extra)).id(),
+            CardBundle::Trap { bundle } => commands.spawn((bundle, extra)).id(),
+        }
+    }
 }
 
 pub trait FromRegistry: Sized {
@@ -136,7 +162,7 @@ impl FromRegistry for CardBundle {
             crate::def::card::CardKindDef::Spell => CardBundle::Spell {
                 bundle: SpellBundle::from_registry(card_registry, card_id)?,
             },
-            crate::def::card::CardKindDef::Trap => CardBundle::Trap {
+            crate::def::card::CardKindDef::Trap(_) => CardBundle::Trap {
                 bundle: TrapBundle::from_registry(card_registry, card_id)?,
             },
         };
@@ -144,19 +170,30 @@ impl FromRegistry for CardBundle {
     }
 }
 
+/// Shuffles every player's deck list into their deck. The deck lists were
+/// checked against the ruleset's deck construction rules when loading
+/// finished.
 pub fn add_cards(
     card_registry: Res<CardRegistry>,
-    players: Query<Entity, With<Player>>,
+    decks: Res<DeckRegistry>,
+    player_decks: Res<PlayerDecks>,
+    mut rng: ResMut<GameRng>,
+    players: Query<(Entity, &Player)>,
     mut commands: Commands,
-) {
-    for player in players {
-        for bundle in DeckBuilder::standard_deck(&card_registry) {
+) -> Result {
+    let mut next_id = InPlayID::new(0);
+    for (player, seat) in players {
+        let name = player_decks
+            .for_seat(seat.number)
+            .ok_or_else(|| GameError::NotFound(format!("deck for player {}", seat.number)))?;
+        let deck = decks
+            .get(name)
+            .ok_or_else(|| GameError::NotFound(format!("deck {name}")))?;
+        for bundle in DeckBuilder::from_list(deck, &card_registry, &mut rng)? {
             let card_id = bundle.card_id();
-            let card_entity = match bundle {
-                CardBundle::Creature { bundle } => commands.spawn((bundle, Owner(player), InDeck { parent: player })).id(),
-                CardBundle::Spell { bundle } => commands.spawn((bundle, Owner(player), InDeck { parent: player })).id(),
-                CardBundle::Trap { bundle } => commands.spawn((bundle, Owner(player), InDeck { parent: player })).id(),
-            };
+            let card_entity =
+                bundle.spawn(&mut commands, (Owner(player), InDeck { parent: player }, next_id));
+            next_id = next_id.next();
 
             let Some(def) = card_registry.get(&card_id) else {
                 continue;
@@ -171,4 +208,5 @@ pub fn add_cards(
             }
         }
     }
+    Ok(())
 }
@@ -7,8 +7,12 @@ use crate::error::GameError;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum Abilities {
+    /// Ignores the movement cost of tile effects and flies over occupied tiles on its path.
     Flying,
+    /// Leaps over occupied tiles on its path.
     Jumping,
+    /// Moves under occupied tiles on its path and can only be attacked from
+    /// an adjacent tile.
     Digging,
 }
 
@@ -16,6 +20,29 @@ pub enum Abilities {
 #[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
 pub struct CardAbilities(pub Vec<Abilities>);
 
+impl CardAbilities {
+    pub fn has(&self, ability: Abilities) -> bool {
+        self.0.contains(&ability)
+    }
+
+    /// Whether moving off a tile costs the usual single point whatever
+    /// effects lie on it.
+    pub fn ignores_movement_cost(&self) -> bool {
+        self.has(Abilities::Flying)
+    }
+
+    /// Whether occupied tiles between the start and the destination of a
+    /// move are no obstacle.
+    pub fn passes_occupants(&self) -> bool {
+        self.has(Abilities::Flying) || self.has(Abilities::Jumping) || self.has(Abilities::Digging)
+    }
+
+    /// Whether only attackers on an adjacent tile can reach this creature.
+    pub fn only_adjacent_attacks(&self) -> bool {
+        self.has(Abilities::Digging)
+    }
+}
+
 impl FromStr for Abilities {
     type Err = GameError;
 
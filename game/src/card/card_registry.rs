@@ -47,10 +47,6 @@ impl CardRegistry {
         ids.sort();
         ids
     }
-
-    pub fn registered_ids(&self) -> std::collections::hash_map::Keys<'_, CardID, CardDef> {
-        self.cards.keys()
-    }
 }
 
 impl Default for CardRegistry {
@@ -12,6 +12,7 @@ use crate::{
     card::{
         Cost, CreatureCard, CurrentAttack, CurrentDefense, CurrentMovementPoints, FromRegistry,
         abilities::CardAbilities, card_id::CardID, card_registry::CardRegistry,
+        modifiers::StatModifiers,
     },
     components::Health,
     def::card::CardKindDef,
@@ -78,6 +79,7 @@ pub struct CreatureBundle {
     pub health: Health,
     pub base_movement_points: BaseMovementPoints,
     pub current_movement_points: CurrentMovementPoints,
+    pub modifiers: StatModifiers,
     pub attack_pattern: AttackPattern,
     pub movement_pattern: MovementPattern,
     pub type_identifier: CreatureCard,
@@ -106,6 +108,7 @@ impl FromRegistry for CreatureBundle {
             health: Health(stats.defense),
             base_movement_points: stats.movement_points.into(),
             current_movement_points: stats.movement_points.into(),
+            modifiers: StatModifiers::default(),
             attack_pattern: Vec::<I16Vec2>::from(&stats.attack_pattern).into(),
             movement_pattern: Vec::<I16Vec2>::from(&stats.movement).into(),
             type_identifier: CreatureCard,
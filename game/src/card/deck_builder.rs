@@ -1,18 +1,250 @@
-use super::{CardBundle, FromRegistry, card_registry::CardRegistry};
+use std::{collections::HashMap, fmt};
+
+use bevy::ecs::resource::Resource;
 use rand::seq::SliceRandom;
 
+use super::{CardBundle, FromRegistry, card_registry::CardRegistry};
+use crate::{GameRng, def::deck::DeckDef, error::GameError, player::SEATS, rules::DeckRules};
+
+/// Every deck list declared in `assets/decks`, by name.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct DeckRegistry(HashMap<String, DeckDef>);
+
+impl DeckRegistry {
+    pub fn insert(&mut self, def: DeckDef) {
+        self.0.insert(def.name.clone(), def);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DeckDef> {
+        self.0.get(name)
+    }
+}
+
+/// The deck list each player plays with, indexed by seat.
+#[derive(Resource, Debug, Clone)]
+pub struct PlayerDecks(pub Vec<String>);
+
+impl PlayerDecks {
+    pub fn for_seat(&self, seat: u8) -> Option<&str> {
+        self.0.get(usize::from(seat)).map(String::as_str)
+    }
+
+    /// Checks that every seat has a deck list that exists and follows the
+    /// deck construction `rules`, reporting every problem at once.
+    pub fn validate(
+        &self,
+        decks: &DeckRegistry,
+        card_registry: &CardRegistry,
+        rules: &DeckRules,
+    ) -> Vec<GameError> {
+        let mut errors = Vec::new();
+        for seat in 0..SEATS {
+            let result = self
+                .for_seat(seat)
+                .ok_or_else(|| GameError::NotFound(format!("deck for player {seat}")))
+                .and_then(|name| {
+                    decks
+                        .get(name)
+                        .ok_or_else(|| GameError::NotFound(format!("deck {name}")))
+                })
+                .and_then(|deck| DeckBuilder::validate(deck, card_registry, rules));
+            errors.extend(result.err());
+        }
+        errors
+    }
+}
+
+impl Default for PlayerDecks {
+    fn default() -> Self {
+        Self(vec!["standard".to_string(), "standard".to_string()])
+    }
+}
+
+/// A reason a deck list breaks the deck construction rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeckViolation {
+    TooFewCards { size: u32, min: u16 },
+    TooManyCards { size: u32, max: u16 },
+    TooManyCopies { card: String, copies: u32, max: u16 },
+    UnknownCard(String),
+}
+
+impl fmt::Display for DeckViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeckViolation::TooFewCards { size, min } => {
+                write!(f, "{} cards, at least {} needed", size, min)
+            }
+            DeckViolation::TooManyCards { size, max } => {
+                write!(f, "{} cards, at most {} allowed", size, max)
+            }
+            DeckViolation::TooManyCopies { card, copies, max } => {
+                write!(f, "{} copies of {}, at most {} allowed", copies, card, max)
+            }
+            DeckViolation::UnknownCard(card) => write!(f, "unknown card {}", card),
+        }
+    }
+}
+
 pub struct DeckBuilder;
 
 impl DeckBuilder {
-    pub fn standard_deck(card_registry: &CardRegistry) -> Vec<CardBundle> {
-        let mut deck = Vec::new();
-        for card_id in card_registry.registered_ids() {
-            let el = CardBundle::from_registry(card_registry, *card_id).unwrap();
-            deck.extend(std::iter::repeat_n(el, 4));
+    /// Checks `deck` against the deck construction `rules`, reporting every
+    /// violation at once.
+    pub fn validate(
+        deck: &DeckDef,
+        card_registry: &CardRegistry,
+        rules: &DeckRules,
+    ) -> Result<(), GameError> {
+        // A card may be listed more than once, so copies are counted by name.
+        let mut copies: Vec<(&str, u32)> = Vec::new();
+        for entry in &deck.cards {
+            match copies.iter_mut().find(|(card, _)| *card == entry.card) {
+                Some((_, count)) => *count += u32::from(entry.count),
+                None => copies.push((&entry.card, u32::from(entry.count))),
+            }
+        }
+        let size: u32 = copies.iter().map(|(_, count)| count).sum();
+
+        let mut violations = Vec::new();
+        if size < u32::from(rules.min_size) {
+            violations.push(DeckViolation::TooFewCards {
+                size,
+                min: rules.min_size,
+            });
+        }
+        if size > u32::from(rules.max_size) {
+            violations.push(DeckViolation::TooManyCards {
+                size,
+                max: rules.max_size,
+            });
+        }
+        for (card, count) in copies {
+            let Some(def) = card_registry
+                .id_of_name(card)
+                .and_then(|id| card_registry.get(&id))
+            else {
+                violations.push(DeckViolation::UnknownCard(card.to_string()));
+                continue;
+            };
+            let max = if def.legendary { 1 } else { rules.max_copies };
+            if count > u32::from(max) {
+                violations.push(DeckViolation::TooManyCopies {
+                    card: card.to_string(),
+                    copies: count,
+                    max,
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(GameError::InvalidDeck {
+                deck: deck.name.clone(),
+                violations,
+            })
+        }
+    }
+
+    /// The cards of `deck` in shuffled order.
+    pub fn from_list(
+        deck: &DeckDef,
+        card_registry: &CardRegistry,
+        rng: &mut GameRng,
+    ) -> Result<Vec<CardBundle>, GameError> {
+        let mut cards = Vec::new();
+        for entry in &deck.cards {
+            let bundle = card_registry
+                .id_of_name(&entry.card)
+                .and_then(|id| CardBundle::from_registry(card_registry, id))
+                .ok_or_else(|| {
+                    GameError::NotFound(format!("card {} in deck {}", entry.card, deck.name))
+                })?;
+            cards.extend(std::iter::repeat_n(bundle, usize::from(entry.count)));
         }
 
-        let mut rng = rand::rng();
-        deck.shuffle(&mut rng);
-        deck
+        cards.shuffle(&mut rng.0);
+        Ok(cards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        card::card_id::CardID,
+        def::{
+            card::{CardDef, CardKindDef},
+            deck::DeckEntryDef,
+        },
+    };
+
+    fn card(name: &str, legendary: bool) -> CardDef {
+        CardDef {
+            name: name.into(),
+            cost: 1,
+            description: String::new(),
+            display_image: String::new(),
+            legendary,
+            kind: CardKindDef::Spell,
+            abilities: vec![],
+        }
+    }
+
+    #[test]
+    fn validation_reports_every_violation() {
+        let mut registry = CardRegistry::new();
+        registry.insert(CardID::new(0), card("soldier", false));
+        registry.insert(CardID::new(1), card("king", true));
+        let entry = |card: &str, count| DeckEntryDef {
+            card: card.into(),
+            count,
+        };
+        let deck = DeckDef {
+            name: "broken".into(),
+            cards: vec![
+                entry("soldier", 3),
+                entry("king", 2),
+                entry("dragon", 1),
+                entry("soldier", 2),
+            ],
+        };
+        let rules = DeckRules {
+            min_size: 10,
+            max_size: 60,
+            max_copies: 4,
+        };
+
+        let Err(GameError::InvalidDeck { deck, violations }) =
+            DeckBuilder::validate(&deck, &registry, &rules)
+        else {
+            panic!("deck should be invalid");
+        };
+        assert_eq!(deck, "broken");
+        assert_eq!(
+            violations,
+            vec![
+                DeckViolation::TooFewCards { size: 8, min: 10 },
+                DeckViolation::TooManyCopies {
+                    card: "soldier".into(),
+                    copies: 5,
+                    max: 4,
+                },
+                DeckViolation::TooManyCopies {
+                    card: "king".into(),
+                    copies: 2,
+                    max: 1,
+                },
+                DeckViolation::UnknownCard("dragon".into()),
+            ]
+        );
+
+        let legal = DeckDef {
+            name: "legal".into(),
+            cards: vec![entry("soldier", 4), entry("king", 1)],
+        };
+        let rules = DeckRules { min_size: 5, ..rules };
+        assert!(DeckBuilder::validate(&legal, &registry, &rules).is_ok());
     }
 }
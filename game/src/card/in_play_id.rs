@@ -1,6 +1,13 @@
 use std::fmt::Display;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+use bevy::ecs::component::Component;
+use serde::{Deserialize, Serialize};
+
+/// Numbers the cards of a game in the order they were dealt, so they can be
+/// referred to across runs of the same game.
+#[derive(
+    Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub struct InPlayID(u32);
 
 impl Display for InPlayID {
@@ -0,0 +1,265 @@
+use bevy::ecs::{component::Component, entity::Entity, query::QueryData, system::Query};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::value_source::StatModifier,
+    card::{
+        CurrentAttack, CurrentDefense, CurrentMovementPoints,
+        creature::{BaseAttack, BaseDefense, BaseMovementPoints},
+    },
+    components::Health,
+};
+
+/// A creature stat that modifiers change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Stat {
+    Attack,
+    MaxHealth,
+    Speed,
+}
+
+/// How long a stat modifier lasts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatDuration {
+    #[default]
+    Permanent,
+    /// Expires after this many turn starts.
+    Turns(u16),
+    /// Expires when the current turn ends.
+    UntilEndOfTurn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatModifierEntry {
+    /// The card or effect that applied the modifier.
+    pub source: Option<Entity>,
+    pub stat: Stat,
+    pub delta: i16,
+    pub duration: StatDuration,
+}
+
+/// Every modifier applied to a creature, oldest first. A creature's current
+/// attack, max health and speed are its base stats plus these modifiers.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatModifiers(Vec<StatModifierEntry>);
+
+impl StatModifiers {
+    pub fn new(entries: Vec<StatModifierEntry>) -> Self {
+        Self(entries)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatModifierEntry> {
+        self.0.iter()
+    }
+
+    /// `base` with every modifier to `stat` applied. The modifiers add up
+    /// before the stat is kept in range, so their order does not matter.
+    pub fn apply(&self, stat: Stat, base: u16) -> u16 {
+        let delta: i32 = self
+            .0
+            .iter()
+            .filter(|entry| entry.stat == stat)
+            .map(|entry| i32::from(entry.delta))
+            .sum();
+        (i32::from(base) + delta).clamp(0, i32::from(u16::MAX)) as u16
+    }
+
+    /// Drops the modifiers that last until the end of the turn. Returns
+    /// whether any were dropped.
+    fn end_turn(&mut self) -> bool {
+        let before = self.0.len();
+        self.0
+            .retain(|entry| entry.duration != StatDuration::UntilEndOfTurn);
+        self.0.len() != before
+    }
+
+    /// Counts timed modifiers down by one turn and drops the ones that ran
+    /// out. Returns whether any were dropped.
+    fn tick(&mut self) -> bool {
+        let before = self.0.len();
+        self.0.retain_mut(|entry| match &mut entry.duration {
+            StatDuration::Turns(turns) => {
+                *turns = turns.saturating_sub(1);
+                *turns > 0
+            }
+            _ => true,
+        });
+        self.0.len() != before
+    }
+}
+
+/// A creature's stats together with the modifiers they are computed from.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct ModifiedCreature {
+    pub modifiers: &'static mut StatModifiers,
+    base_attack: &'static BaseAttack,
+    base_defense: &'static BaseDefense,
+    base_speed: &'static BaseMovementPoints,
+    attack: &'static mut CurrentAttack,
+    defense: &'static mut CurrentDefense,
+    health: &'static mut Health,
+    speed: &'static mut CurrentMovementPoints,
+}
+
+impl ModifiedCreatureItem<'_, '_> {
+    /// Adds `modifier` to the creature's stack. Health and speed modifiers
+    /// also change the creature's current health and remaining movement
+    /// points by the same amount.
+    pub fn modify(
+        &mut self,
+        modifier: StatModifier,
+        duration: StatDuration,
+        source: Option<Entity>,
+    ) {
+        let (stats, health, speed): (&[(Stat, i16)], i16, i16) = match modifier {
+            StatModifier::Attack(d) => (&[(Stat::Attack, d)], 0, 0),
+            StatModifier::Health(d) => (&[(Stat::MaxHealth, d)], d, 0),
+            StatModifier::MaxHealth(d) => (&[(Stat::MaxHealth, d)], 0, 0),
+            StatModifier::Speed(d) => (&[(Stat::Speed, d)], 0, d),
+            StatModifier::Both { attack, health } => (
+                &[(Stat::Attack, attack), (Stat::MaxHealth, health)],
+                health,
+                0,
+            ),
+        };
+        for &(stat, delta) in stats {
+            self.modifiers.0.push(StatModifierEntry {
+                source,
+                stat,
+                delta,
+                duration,
+            });
+        }
+        self.defense.0 = self.defense.0.saturating_add_signed(health);
+        self.speed.0 = self.speed.0.saturating_add_signed(speed);
+        self.recompute();
+    }
+
+    /// Recomputes attack and max health from the base stats and modifiers,
+    /// keeping current health and movement points within their new maximum.
+    fn recompute(&mut self) {
+        self.attack.0 = self.modifiers.apply(Stat::Attack, self.base_attack.0);
+        self.health
+            .set_value(self.modifiers.apply(Stat::MaxHealth, self.base_defense.0));
+        self.defense.0 = self.defense.0.min(self.health.value());
+        let max_speed = self.modifiers.apply(Stat::Speed, self.base_speed.0);
+        self.speed.0 = self.speed.0.min(max_speed);
+    }
+}
+
+/// Drops the modifiers that only last for the turn that just ended.
+pub(crate) fn expire_end_of_turn_modifiers(mut creatures: Query<ModifiedCreature>) {
+    for mut creature in &mut creatures {
+        if creature.modifiers.end_turn() {
+            creature.recompute();
+        }
+    }
+}
+
+/// Counts timed modifiers down at the start of every turn.
+pub(crate) fn tick_stat_modifiers(mut creatures: Query<ModifiedCreature>) {
+    for mut creature in &mut creatures {
+        if creature.modifiers.tick() {
+            creature.recompute();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{ecs::system::RunSystemOnce, prelude::World};
+
+    fn spawn_creature(world: &mut World) -> Entity {
+        world
+            .spawn((
+                StatModifiers::default(),
+                BaseAttack(2),
+                BaseDefense(3),
+                BaseMovementPoints(1),
+                CurrentAttack(2),
+                CurrentDefense(3),
+                Health(3),
+                CurrentMovementPoints(1),
+            ))
+            .id()
+    }
+
+    fn modify(world: &mut World, creature: Entity, modifier: StatModifier, duration: StatDuration) {
+        world
+            .run_system_once(move |mut creatures: Query<ModifiedCreature>| {
+                creatures
+                    .get_mut(creature)
+                    .unwrap()
+                    .modify(modifier, duration, None);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn max_health_modifiers_stick_and_temporary_buffs_expire() {
+        let mut world = World::new();
+        let world = &mut world;
+        let creature = spawn_creature(world);
+
+        modify(
+            world,
+            creature,
+            StatModifier::MaxHealth(2),
+            StatDuration::Permanent,
+        );
+        modify(
+            world,
+            creature,
+            StatModifier::Both {
+                attack: 3,
+                health: 1,
+            },
+            StatDuration::UntilEndOfTurn,
+        );
+        modify(
+            world,
+            creature,
+            StatModifier::Attack(1),
+            StatDuration::Turns(2),
+        );
+        assert_eq!(world.get::<CurrentAttack>(creature).unwrap().0, 6);
+        assert_eq!(world.get::<Health>(creature).unwrap().value(), 6);
+        assert_eq!(world.get::<CurrentDefense>(creature).unwrap().0, 4);
+
+        world.run_system_once(expire_end_of_turn_modifiers).unwrap();
+        assert_eq!(world.get::<CurrentAttack>(creature).unwrap().0, 3);
+        assert_eq!(world.get::<Health>(creature).unwrap().value(), 5);
+        assert_eq!(world.get::<CurrentDefense>(creature).unwrap().0, 4);
+
+        world.run_system_once(tick_stat_modifiers).unwrap();
+        assert_eq!(world.get::<CurrentAttack>(creature).unwrap().0, 3);
+        world.run_system_once(tick_stat_modifiers).unwrap();
+        assert_eq!(world.get::<CurrentAttack>(creature).unwrap().0, 2);
+        assert_eq!(world.get::<Health>(creature).unwrap().value(), 5);
+    }
+
+    #[test]
+    fn modifiers_add_up_before_the_stat_is_kept_in_range() {
+        let entry = |delta| StatModifierEntry {
+            source: None,
+            stat: Stat::Attack,
+            delta,
+            duration: StatDuration::Permanent,
+        };
+        let debuffed_first = StatModifiers::new(vec![entry(-3), entry(3)]);
+        let buffed_first = StatModifiers::new(vec![entry(3), entry(-3)]);
+        assert_eq!(debuffed_first.apply(Stat::Attack, 2), 2);
+        assert_eq!(buffed_first.apply(Stat::Attack, 2), 2);
+        assert_eq!(debuffed_first.apply(Stat::Speed, 2), 2);
+        assert_eq!(
+            StatModifiers::new(vec![entry(-3)]).apply(Stat::Attack, 2),
+            0
+        );
+        assert_eq!(
+            StatModifiers::new(vec![entry(i16::MAX); 3]).apply(Stat::Attack, 2),
+            u16::MAX
+        );
+    }
+}
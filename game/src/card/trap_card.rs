@@ -1,19 +1,25 @@
 use bevy::{
-    ecs::{bundle::Bundle, name::Name},
+    ecs::{bundle::Bundle, component::Component, name::Name},
     log::warn,
 };
 
 use crate::{
     card::{Cost, FromRegistry, TrapCard, card_id::CardID, card_registry::CardRegistry},
-    def::card::CardKindDef,
+    def::{card::CardKindDef, condition::ConditionDef},
 };
 
+/// Condition that reveals a set trap on its own. Traps without one only
+/// reveal when an enemy creature enters their tile.
+#[derive(Component, Clone, Debug)]
+pub struct RevealCondition(pub ConditionDef);
+
 #[derive(Bundle, Clone)]
 pub struct TrapBundle {
     pub card_id: CardID,
     pub name: Name,
     pub cost: Cost,
     pub type_identifier: TrapCard,
+    pub reveal_condition: RevealCondition,
 }
 
 impl FromRegistry for TrapBundle {
@@ -22,7 +28,7 @@ impl FromRegistry for TrapBundle {
             warn!("Card Id {} not found", card_id);
             return None;
         };
-        let CardKindDef::Trap = &def.kind else {
+        let CardKindDef::Trap(trap) = &def.kind else {
             warn!("Card Id {} is not a trap", card_id);
             return None;
         };
@@ -32,6 +38,9 @@ impl FromRegistry for TrapBundle {
             name: def.name.as_str().into(),
             cost: def.cost.into(),
             type_identifier: TrapCard,
+            reveal_condition: RevealCondition(
+                trap.reveal_condition.clone().unwrap_or(ConditionDef::Never),
+            ),
         })
     }
 }
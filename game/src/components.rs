@@ -4,10 +4,6 @@ use bevy::prelude::*;
 pub struct Health(pub(crate) u16);
 
 impl Health {
-    pub fn player_base_health() -> Self {
-        Self(10)
-    }
-
     pub fn value(&self) -> u16 {
         self.0
     }
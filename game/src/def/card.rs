@@ -1,9 +1,9 @@
 use bevy::{asset::Asset, reflect::TypePath};
 use serde::{Deserialize, Serialize};
 
-use crate::card::abilities::Abilities;
+use crate::{board::effect::EffectType, card::abilities::Abilities};
 
-use super::trigger::AbilityDef;
+use super::{condition::ConditionDef, trigger::AbilityDef};
 
 /// A card definition loaded from a `.ron` file in `assets/cards`.
 #[derive(Asset, TypePath, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -14,17 +14,43 @@ pub struct CardDef {
     pub description: String,
     #[serde(default)]
     pub display_image: String,
+    /// A deck may hold only one copy of a legendary card.
+    #[serde(default)]
+    pub legendary: bool,
     pub kind: CardKindDef,
     /// Triggered abilities of the card.
     #[serde(default)]
     pub abilities: Vec<AbilityDef>,
 }
 
+impl CardDef {
+    /// The tile effects the card's abilities lay or check for.
+    pub fn effect_types(&self) -> Vec<&EffectType> {
+        let reveal = match &self.kind {
+            CardKindDef::Trap(trap) => trap.reveal_condition.as_ref(),
+            _ => None,
+        };
+        reveal
+            .into_iter()
+            .flat_map(ConditionDef::effect_types)
+            .chain(self.abilities.iter().flat_map(AbilityDef::effect_types))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CardKindDef {
     Creature(CreatureStatsDef),
     Spell,
-    Trap,
+    Trap(TrapDef),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrapDef {
+    /// Reveals the trap as soon as the condition holds, in addition to an
+    /// enemy creature entering its tile.
+    #[serde(default)]
+    pub reveal_condition: Option<ConditionDef>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
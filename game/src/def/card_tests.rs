@@ -4,7 +4,14 @@
 
 #[cfg(test)]
 mod card_tests {
-    use crate::def::card::{CardDef, CardKindDef};
+    use crate::def::{
+        card::{CardDef, CardKindDef},
+        deck::DeckDef,
+        tile_effect::TileEffectDef,
+    };
+    use crate::board::effect::{EffectRegistry, EffectType};
+    use crate::card::{card_id::CardID, card_registry::CardRegistry, deck_builder::DeckBuilder};
+    use crate::rules::Ruleset;
     use std::path::{Path, PathBuf};
 
     /// Path to `assets/cards` relative to the crate manifest.
@@ -14,6 +21,7 @@ mod card_tests {
 
     #[test]
     fn all_cards_parse_and_match_filename() {
+        let effects = effect_registry();
         let dir = cards_dir();
         assert!(
             dir.exists(),
@@ -55,8 +63,11 @@ mod card_tests {
                     assert!(stats.attack > 0, "creature {:?} attack 0", card.name);
                     assert!(stats.defense > 0, "creature {:?} defense 0", card.name);
                 }
-                CardKindDef::Spell | CardKindDef::Trap => {}
+                CardKindDef::Spell | CardKindDef::Trap(_) => {}
             }
+            effects.check(card.effect_types()).unwrap_or_else(|e| {
+                panic!("card {:?} names an undeclared effect: {}", card.name, e)
+            });
 
             parsed_any = true;
             eprintln!("parsed card {:?} (cost {})", card.name, card.cost);
@@ -64,4 +75,118 @@ mod card_tests {
 
         assert!(parsed_any, "no .ron cards found in {:?}", dir);
     }
+
+    /// Declares every effect in `assets/effects`, as cards name them.
+    fn effect_registry() -> EffectRegistry {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join("effects");
+
+        let mut registry = EffectRegistry::default();
+        for entry in std::fs::read_dir(&dir).expect("read assets/effects") {
+            let path = entry.expect("dir entry").path();
+            let Some(stem) = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.strip_suffix(".effect.ron"))
+            else {
+                continue;
+            };
+
+            let src = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("read {:?}: {}", path, e));
+            let effect: TileEffectDef = ron::de::from_str(&src)
+                .unwrap_or_else(|e| panic!("failed to parse {:?}: {}", path, e));
+
+            assert_eq!(
+                effect.name.name().to_lowercase(),
+                stem,
+                "effect name does not match filename stem"
+            );
+            registry.insert(effect);
+        }
+        registry
+    }
+
+    #[test]
+    fn all_effects_parse_and_match_filename() {
+        let registry = effect_registry();
+        assert!(
+            registry.get(&EffectType::new("Burning")).is_some(),
+            "no Burning effect found in assets/effects"
+        );
+    }
+
+    #[test]
+    fn all_rulesets_parse_and_standard_matches_defaults() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join("rules");
+
+        let mut standard = None;
+        for entry in std::fs::read_dir(&dir).expect("read assets/rules") {
+            let path = entry.expect("dir entry").path();
+            let Some(stem) = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.strip_suffix(".ruleset.ron"))
+            else {
+                continue;
+            };
+
+            let src = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("read {:?}: {}", path, e));
+            let rules: Ruleset = ron::de::from_str(&src)
+                .unwrap_or_else(|e| panic!("failed to parse {:?}: {}", path, e));
+            if stem == "standard" {
+                standard = Some(rules);
+            }
+        }
+
+        assert_eq!(standard, Some(Ruleset::default()));
+    }
+
+    #[test]
+    fn all_decks_parse_and_are_legal_in_the_standard_ruleset() {
+        let mut registry = CardRegistry::new();
+        for (index, entry) in std::fs::read_dir(cards_dir())
+            .expect("read assets/cards")
+            .enumerate()
+        {
+            let path = entry.expect("dir entry").path();
+            let src = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("read {:?}: {}", path, e));
+            let card: CardDef = ron::de::from_str(&src)
+                .unwrap_or_else(|e| panic!("failed to parse {:?}: {}", path, e));
+            registry.insert(CardID::new(index as u32), card);
+        }
+
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join("decks");
+
+        let mut parsed_any = false;
+        for entry in std::fs::read_dir(&dir).expect("read assets/decks") {
+            let path = entry.expect("dir entry").path();
+            let Some(stem) = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.strip_suffix(".deck.ron"))
+            else {
+                continue;
+            };
+
+            let src = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("read {:?}: {}", path, e));
+            let deck: DeckDef = ron::de::from_str(&src)
+                .unwrap_or_else(|e| panic!("failed to parse {:?}: {}", path, e));
+
+            assert_eq!(deck.name, stem, "deck name does not match filename stem");
+            DeckBuilder::validate(&deck, &registry, &Ruleset::default().deck)
+                .unwrap_or_else(|e| panic!("{:?}: {}", path, e));
+            parsed_any = true;
+        }
+
+        assert!(parsed_any, "no .deck.ron files found in {:?}", dir);
+    }
 }
@@ -35,6 +35,22 @@ pub enum ConditionDef {
     Not(Box<ConditionDef>),
 }
 
+impl ConditionDef {
+    /// The tile effects the condition checks for.
+    pub fn effect_types(&self) -> Vec<&EffectType> {
+        match self {
+            ConditionDef::HasEffect { effect, .. } => vec![effect],
+            ConditionDef::And(left, right) | ConditionDef::Or(left, right) => {
+                let mut effects = left.effect_types();
+                effects.extend(right.effect_types());
+                effects
+            }
+            ConditionDef::Not(condition) => condition.effect_types(),
+            _ => Vec::new(),
+        }
+    }
+}
+
 impl Default for ConditionDef {
     fn default() -> Self {
         Self::Always
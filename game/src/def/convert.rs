@@ -154,7 +154,7 @@ impl TryFrom<&ConditionDef> for Condition {
                 match any {
                     AnyTargetSelector::TileSingle(s) => Ok(Condition::HasEffect {
                         selector: s,
-                        effect: *effect,
+                        effect: effect.clone(),
                     }),
                     _ => Err(DefError::InvalidCondition(
                         "HasEffect requires a single tile selector".into(),
@@ -0,0 +1,16 @@
+use bevy::{asset::Asset, reflect::TypePath};
+use serde::{Deserialize, Serialize};
+
+/// A deck list loaded from a `.deck.ron` file in `assets/decks`.
+#[derive(Asset, TypePath, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckDef {
+    pub name: String,
+    pub cards: Vec<DeckEntryDef>,
+}
+
+/// `count` copies of the card registered as `card`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckEntryDef {
+    pub card: String,
+    pub count: u16,
+}
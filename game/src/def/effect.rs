@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::board::effect::EffectType;
+use crate::{board::effect::EffectType, card::modifiers::StatDuration};
 
 use super::{condition::ConditionDef, selector::SelectorDef, value::ValueDef};
 
@@ -35,6 +35,8 @@ pub enum EffectDef {
     ModifyStats {
         selector: SelectorDef,
         modifier: StatModifierDef,
+        #[serde(default)]
+        duration: StatDuration,
     },
     /// Forced move of the creature(s) standing on the selected tile(s)
     /// by `direction` (or to an absolute board position when
@@ -71,6 +73,34 @@ pub enum EffectDef {
     Choose { options: Vec<ChoiceOptionDef> },
 }
 
+impl EffectDef {
+    /// The tile effects the effect lays or checks for, in its branches too.
+    pub fn effect_types(&self) -> Vec<&EffectType> {
+        match self {
+            EffectDef::ApplyEffect { effect, .. } => vec![effect],
+            EffectDef::If {
+                condition,
+                then,
+                otherwise,
+            } => condition
+                .effect_types()
+                .into_iter()
+                .chain(
+                    then.iter()
+                        .chain(otherwise)
+                        .flat_map(EffectDef::effect_types),
+                )
+                .collect(),
+            EffectDef::Choose { options } => options
+                .iter()
+                .flat_map(|option| &option.effects)
+                .flat_map(EffectDef::effect_types)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChoiceOptionDef {
     pub label: String,
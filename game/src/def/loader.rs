@@ -1,14 +1,28 @@
-//! Asset loading for `CardDef` RON files and the card registry setup plugin.
+//! Asset loading for `CardDef`, `TileEffectDef`, `DeckDef` and `Ruleset` RON
+//! files and the plugin setting up their registries.
+
+use std::marker::PhantomData;
 
 use bevy::asset::{
-    io::Reader, AssetLoader, AssetServer, Assets, Handle, LoadContext, LoadedFolder,
+    io::Reader, AssetLoader, AssetServer, Assets, Handle, LoadContext, LoadState as AssetLoadState,
+    LoadedFolder, RecursiveDependencyLoadState,
 };
 use bevy::prelude::*;
 use bevy::tasks::ConditionalSendFuture;
+use serde::de::DeserializeOwned;
 
-use crate::card::{card_id::CardID, card_registry::CardRegistry};
+use crate::{
+    board::effect::EffectRegistry,
+    card::{
+        card_id::CardID,
+        card_registry::CardRegistry,
+        deck_builder::{DeckRegistry, PlayerDecks},
+    },
+    error::GameError,
+    rules::Ruleset,
+};
 
-use super::card::CardDef;
+use super::{card::CardDef, deck::DeckDef, tile_effect::TileEffectDef};
 
 /// Loading state for card assets.
 #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
@@ -22,44 +36,85 @@ pub enum LoadState {
 #[derive(Resource)]
 pub struct LoadedCards(pub Handle<LoadedFolder>);
 
-/// Custom asset loader for `.ron` card definitions.
-#[derive(Default, TypePath)]
-pub struct RonCardLoader;
+/// Handle to the loaded `effects/` folder.
+#[derive(Resource)]
+pub struct LoadedEffects(pub Handle<LoadedFolder>);
+
+/// Handle to the loaded `decks/` folder.
+#[derive(Resource)]
+pub struct LoadedDecks(pub Handle<LoadedFolder>);
+
+/// Path of the ruleset to play with, relative to `assets`.
+#[derive(Resource, Debug, Clone)]
+pub struct RulesetPath(pub String);
+
+impl RulesetPath {
+    /// The ruleset `assets/rules/<name>.ruleset.ron`.
+    pub fn named(name: &str) -> Self {
+        Self(format!("rules/{name}.ruleset.ron"))
+    }
+}
+
+impl Default for RulesetPath {
+    fn default() -> Self {
+        Self::named("standard")
+    }
+}
+
+/// Handle to the loaded ruleset.
+#[derive(Resource)]
+pub struct LoadedRuleset(pub Handle<Ruleset>);
+
+/// Asset loader deserializing RON files with the given extensions into `A`.
+#[derive(TypePath)]
+pub struct RonLoader<A> {
+    extensions: &'static [&'static str],
+    asset: PhantomData<fn() -> A>,
+}
+
+impl<A> RonLoader<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            asset: PhantomData,
+        }
+    }
+}
 
-/// Errors that can occur while loading a RON card.
+/// Errors that can occur while loading a RON asset.
 #[derive(Debug)]
-pub enum RonCardLoaderError {
+pub enum RonLoaderError {
     Io(std::io::Error),
     Ron(ron::error::SpannedError),
 }
 
-impl std::fmt::Display for RonCardLoaderError {
+impl std::fmt::Display for RonLoaderError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            RonCardLoaderError::Io(e) => write!(f, "io error: {}", e),
-            RonCardLoaderError::Ron(e) => write!(f, "ron error: {}", e),
+            RonLoaderError::Io(e) => write!(f, "io error: {}", e),
+            RonLoaderError::Ron(e) => write!(f, "ron error: {}", e),
         }
     }
 }
 
-impl std::error::Error for RonCardLoaderError {}
+impl std::error::Error for RonLoaderError {}
 
-impl From<std::io::Error> for RonCardLoaderError {
+impl From<std::io::Error> for RonLoaderError {
     fn from(value: std::io::Error) -> Self {
         Self::Io(value)
     }
 }
 
-impl From<ron::error::SpannedError> for RonCardLoaderError {
+impl From<ron::error::SpannedError> for RonLoaderError {
     fn from(value: ron::error::SpannedError) -> Self {
         Self::Ron(value)
     }
 }
 
-impl AssetLoader for RonCardLoader {
-    type Asset = CardDef;
+impl<A: Asset + DeserializeOwned> AssetLoader for RonLoader<A> {
+    type Asset = A;
     type Settings = ();
-    type Error = RonCardLoaderError;
+    type Error = RonLoaderError;
 
     fn load(
         &self,
@@ -76,22 +131,172 @@ impl AssetLoader for RonCardLoader {
     }
 
     fn extensions(&self) -> &[&str] {
-        &["ron"]
+        self.extensions
     }
 }
 
-fn startup_load_cards(asset_server: Res<AssetServer>, mut commands: Commands) {
-    commands.insert_resource(LoadedCards(asset_server.load_folder("cards")));
+fn startup_load_cards(
+    asset_server: Res<AssetServer>,
+    ruleset: Res<RulesetPath>,
+    mut commands: Commands,
+) {
+    commands.insert_resource(LoadedEffects(asset_server.load_folder("effects")));
+    commands.insert_resource(LoadedDecks(asset_server.load_folder("decks")));
+    commands.insert_resource(LoadedRuleset(asset_server.load(ruleset.0.clone())));
 }
 
+/// Makes the loaded ruleset the one the game is played with.
+fn apply_ruleset(
+    loaded: Res<LoadedRuleset>,
+    rulesets: Res<Assets<Ruleset>>,
+    mut rules: ResMut<Ruleset>,
+) {
+    if let Some(ruleset) = rulesets.get(&loaded.0)
+        && *rules != *ruleset
+    {
+        *rules = ruleset.clone();
+    }
+}
+
+/// Whether loading `handle` failed, logging why.
+fn load_failed(asset_server: &AssetServer, handle: &Handle<LoadedFolder>, what: &str) -> bool {
+    match asset_server.recursive_dependency_load_state(handle) {
+        RecursiveDependencyLoadState::Failed(e) => {
+            error!("Could not load the {what}: {e}");
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Fills the effect registry once the `effects/` folder is loaded, then
+/// loads the cards naming those effects. An effect naming an undeclared
+/// effect ends the game.
+fn build_effect_registry(
+    asset_server: Res<AssetServer>,
+    folder: Res<LoadedEffects>,
+    loaded_folders: Res<Assets<LoadedFolder>>,
+    effect_assets: Res<Assets<TileEffectDef>>,
+    mut registry: ResMut<EffectRegistry>,
+    cards: Option<Res<LoadedCards>>,
+    mut commands: Commands,
+) {
+    if load_failed(&asset_server, &folder.0, "tile effects") {
+        commands.write_message(AppExit::error());
+        return;
+    }
+    if !asset_server.is_loaded_with_dependencies(&folder.0) {
+        return;
+    }
+    let Some(folder) = loaded_folders.get(&folder.0) else {
+        return;
+    };
+
+    let defs: Vec<&TileEffectDef> = folder
+        .handles
+        .iter()
+        .filter_map(|untyped| effect_assets.get(&untyped.clone().typed::<TileEffectDef>()))
+        .collect();
+    let mut new_registry = EffectRegistry::default();
+    for def in &defs {
+        new_registry.insert((*def).clone());
+    }
+    // Effects may lay other effects, declared in any file of the folder.
+    for def in defs {
+        if let Err(e) = new_registry.check(def.effect_types()) {
+            error!("Tile effect {:?} cannot be loaded: {e}", def.name);
+            commands.write_message(AppExit::error());
+            return;
+        }
+    }
+    *registry = new_registry;
+    if cards.is_none() {
+        commands.insert_resource(LoadedCards(asset_server.load_folder("cards")));
+    }
+}
+
+/// Gives the declared effects the movement costs the ruleset sets. A ruleset
+/// naming an undeclared effect ends the game.
+fn apply_movement_costs(
+    rules: Res<Ruleset>,
+    mut registry: ResMut<EffectRegistry>,
+    mut commands: Commands,
+) {
+    if let Err(e) = registry.check(rules.movement_costs.keys()) {
+        error!("The ruleset cannot be played: {e}");
+        commands.write_message(AppExit::error());
+        return;
+    }
+    registry.set_movement_costs(&rules.movement_costs);
+}
+
+/// Fills the deck registry once the `decks/` folder is loaded.
+fn build_deck_registry(
+    asset_server: Res<AssetServer>,
+    folder: Res<LoadedDecks>,
+    loaded_folders: Res<Assets<LoadedFolder>>,
+    deck_assets: Res<Assets<DeckDef>>,
+    mut registry: ResMut<DeckRegistry>,
+) {
+    if !asset_server.is_loaded_with_dependencies(&folder.0) {
+        return;
+    }
+    let Some(folder) = loaded_folders.get(&folder.0) else {
+        return;
+    };
+
+    let mut new_registry = DeckRegistry::default();
+    for untyped in &folder.handles {
+        if let Some(def) = deck_assets.get(&untyped.clone().typed::<DeckDef>()) {
+            new_registry.insert(def.clone());
+        }
+    }
+    *registry = new_registry;
+}
+
+/// Plays with the standard rules when the chosen ruleset does not load.
+fn fall_back_to_default_ruleset(
+    asset_server: Res<AssetServer>,
+    path: Res<RulesetPath>,
+    mut loaded: ResMut<LoadedRuleset>,
+    mut rulesets: ResMut<Assets<Ruleset>>,
+) {
+    if let AssetLoadState::Failed(e) = asset_server.load_state(&loaded.0) {
+        error!(
+            "Could not load the ruleset {}: {e}. Playing with the standard rules instead",
+            path.0
+        );
+        loaded.0 = rulesets.add(Ruleset::default());
+    }
+}
+
+/// The game is set up from the ruleset once loading is done, so loading
+/// waits for it.
+fn ruleset_loaded(rulesets: Res<Assets<Ruleset>>, ruleset: Res<LoadedRuleset>) -> bool {
+    rulesets.contains(&ruleset.0)
+}
+
+/// Players are dealt their decks once loading is done, so loading waits for
+/// the deck lists as well.
+fn decks_loaded(asset_server: Res<AssetServer>, decks: Res<LoadedDecks>) -> bool {
+    asset_server.is_loaded_with_dependencies(&decks.0)
+}
+
+/// Fills the card registry once the `cards/` folder is loaded. A card that
+/// does not load or names an unknown tile effect ends the game.
 fn build_card_registry(
     asset_server: Res<AssetServer>,
     folder: Res<LoadedCards>,
     loaded_folders: Res<Assets<LoadedFolder>>,
     card_assets: Res<Assets<CardDef>>,
+    effects: Res<EffectRegistry>,
     mut registry: ResMut<CardRegistry>,
-    mut next_state: ResMut<NextState<LoadState>>,
+    mut exit: MessageWriter<AppExit>,
 ) {
+    if load_failed(&asset_server, &folder.0, "cards") {
+        exit.write(AppExit::error());
+        return;
+    }
     if !asset_server.is_loaded_with_dependencies(&folder.0) {
         return;
     }
@@ -110,6 +315,11 @@ fn build_card_registry(
             continue;
         };
         let key = path.path().to_string_lossy().to_string();
+        if let Err(e) = effects.check(def.effect_types()) {
+            error!("Card {key} cannot be loaded: {e}");
+            exit.write(AppExit::error());
+            return;
+        }
         entries.push((key, def.clone()));
     }
 
@@ -121,7 +331,39 @@ fn build_card_registry(
     }
 
     *registry = new_registry;
-    next_state.set(LoadState::Ready);
+}
+
+fn cards_loaded(asset_server: Res<AssetServer>, cards: Option<Res<LoadedCards>>) -> bool {
+    cards.is_some_and(|cards| asset_server.is_loaded_with_dependencies(&cards.0))
+}
+
+/// Starts the game once the cards are registered, unless a player's deck
+/// list is missing or breaks the ruleset's deck construction rules. Then
+/// every problem is logged and the game ends instead.
+fn check_player_decks(
+    cards: Res<CardRegistry>,
+    decks: Res<DeckRegistry>,
+    player_decks: Res<PlayerDecks>,
+    rules: Res<Ruleset>,
+    mut next_state: ResMut<NextState<LoadState>>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let errors = player_decks.validate(&decks, &cards, &rules.deck);
+    if errors.is_empty() {
+        next_state.set(LoadState::Ready);
+        return;
+    }
+    for e in errors {
+        match e {
+            GameError::InvalidDeck { deck, violations } => {
+                for violation in violations {
+                    error!("Deck {deck} is invalid: {violation}");
+                }
+            }
+            e => error!("Cannot deal the decks: {e}"),
+        }
+    }
+    exit.write(AppExit::error());
 }
 
 pub struct CardPlugin;
@@ -129,12 +371,36 @@ pub struct CardPlugin;
 impl Plugin for CardPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<CardDef>()
-            .register_asset_loader(RonCardLoader)
+            .init_asset::<TileEffectDef>()
+            .init_asset::<DeckDef>()
+            .init_asset::<Ruleset>()
+            .register_asset_loader(RonLoader::<CardDef>::new(&["ron"]))
+            .register_asset_loader(RonLoader::<TileEffectDef>::new(&["effect.ron"]))
+            .register_asset_loader(RonLoader::<DeckDef>::new(&["deck.ron"]))
+            .register_asset_loader(RonLoader::<Ruleset>::new(&["ruleset.ron"]))
+            .init_resource::<EffectRegistry>()
+            .init_resource::<DeckRegistry>()
+            .init_resource::<PlayerDecks>()
+            .init_resource::<Ruleset>()
+            .init_resource::<RulesetPath>()
             .init_state::<LoadState>()
             .add_systems(Startup, startup_load_cards)
             .add_systems(
                 Update,
-                build_card_registry.run_if(in_state(LoadState::Loading)),
+                (
+                    fall_back_to_default_ruleset,
+                    apply_ruleset,
+                    build_effect_registry,
+                    apply_movement_costs.run_if(resource_exists::<LoadedCards>),
+                    build_deck_registry,
+                    (build_card_registry, check_player_decks.run_if(cards_loaded))
+                        .chain()
+                        .run_if(resource_exists::<LoadedCards>)
+                        .run_if(ruleset_loaded)
+                        .run_if(decks_loaded),
+                )
+                    .chain()
+                    .run_if(in_state(LoadState::Loading)),
             );
     }
 }
@@ -1,14 +1,17 @@
 //! Data-level intermediate representation (IR) for card definitions.
 //!
 //! `CardDef` and friends are plain serde-deserializable data loaded from RON
-//! files in `assets/cards`. They are converted into the runtime ECS model
-//! (`ValueSource`, `Condition`, `AnyTargetSelector`, ...) by [`convert`].
+//! files in `assets/cards` and `assets/effects`. They are converted into the
+//! runtime ECS model (`ValueSource`, `Condition`, `AnyTargetSelector`, ...)
+//! by [`convert`].
 pub mod card;
 pub mod condition;
 pub mod convert;
+pub mod deck;
 pub mod effect;
 pub mod loader;
 pub mod selector;
+pub mod tile_effect;
 pub mod trigger;
 pub mod value;
 pub mod value_expr;
@@ -19,17 +22,18 @@ mod card_tests;
 #[cfg(test)]
 mod ron_roundtrip_tests {
     use super::{
-        card::{CardDef, CardKindDef, CreatureStatsDef, PatternDef},
+        card::{CardDef, CardKindDef, CreatureStatsDef, PatternDef, TrapDef},
         condition::{ConditionDef, CreatureConditionDef, PlayerConditionDef},
         effect::{ChoiceOptionDef, EffectDef, StatModifierDef},
         selector::{CardinalityDef, FilterDef, SelectionDef, SelectorDef, SelectorKindDef},
-        trigger::{AbilityDef, TriggerDef},
+        tile_effect::TileEffectDef,
+        trigger::{AbilityDef, HookDef, ReplacementDef, TriggerDef},
         value::ValueDef,
     };
     use crate::{
         actions::{conditions::CompareOp, value_source::StatType},
-        board::effect::EffectType,
-        card::abilities::Abilities,
+        board::effect::{Affects, EffectRegistry, EffectRule, EffectTicks, EffectType},
+        card::{abilities::Abilities, modifiers::StatDuration},
     };
 
     fn roundtrip<T>(value: &T)
@@ -113,7 +117,7 @@ mod ron_roundtrip_tests {
         });
         roundtrip(&ConditionDef::HasEffect {
             selector: sample_selector(),
-            effect: EffectType::Weakening,
+            effect: EffectType::new("Weakening"),
         });
         roundtrip(&ConditionDef::Player(PlayerConditionDef::HasMinGold {
             player: sample_selector(),
@@ -136,7 +140,7 @@ mod ron_roundtrip_tests {
         });
         roundtrip(&EffectDef::ApplyEffect {
             selector: sample_selector(),
-            effect: EffectType::Slow,
+            effect: EffectType::new("Slow"),
             duration: 2,
         });
         roundtrip(&EffectDef::ModifyStats {
@@ -145,6 +149,7 @@ mod ron_roundtrip_tests {
                 attack: 1,
                 health: -1,
             },
+            duration: StatDuration::UntilEndOfTurn,
         });
         roundtrip(&EffectDef::MoveCreature {
             selector: sample_selector(),
@@ -168,6 +173,7 @@ mod ron_roundtrip_tests {
                     effects: vec![EffectDef::ModifyStats {
                         selector: sample_selector(),
                         modifier: StatModifierDef::Attack(2),
+                        duration: StatDuration::Turns(2),
                     }],
                 },
                 ChoiceOptionDef {
@@ -192,9 +198,15 @@ mod ron_roundtrip_tests {
                 player: sample_selector(),
                 amount: ValueDef::Constant(4),
             }],
+            replacement: None,
         });
         roundtrip(&TriggerDef::OnPlay);
         roundtrip(&TriggerDef::OnReveal);
+        roundtrip(&TriggerDef::Before {
+            event: HookDef::Damaged,
+            once_per_turn: true,
+        });
+        roundtrip(&ReplacementDef::Reduce(ValueDef::Constant(1)));
     }
 
     #[test]
@@ -204,6 +216,7 @@ mod ron_roundtrip_tests {
             cost: 1,
             description: "A soldier".into(),
             display_image: "missing".into(),
+            legendary: true,
             kind: CardKindDef::Creature(CreatureStatsDef {
                 attack: 3,
                 defense: 3,
@@ -219,6 +232,7 @@ mod ron_roundtrip_tests {
             cost: 2,
             description: "Blows away a card".into(),
             display_image: "missing".into(),
+            legendary: false,
             kind: CardKindDef::Spell,
             abilities: vec![AbilityDef {
                 trigger: TriggerDef::OnPlay,
@@ -230,9 +244,66 @@ mod ron_roundtrip_tests {
                     direction: [1, 0],
                     absolute: false,
                 }],
+                replacement: None,
             }],
         });
-        roundtrip(&CardKindDef::Trap);
+        roundtrip(&CardKindDef::Trap(TrapDef::default()));
+        roundtrip(&CardKindDef::Trap(TrapDef {
+            reveal_condition: Some(ConditionDef::Never),
+        }));
         roundtrip(&PatternDef::Offsets(vec![[5, 0], [-2, 3]]));
     }
+
+    #[test]
+    fn tile_effect_def_roundtrip() {
+        roundtrip(&TileEffectDef {
+            name: EffectType::new("Burning"),
+            description: "Burns".into(),
+            display_image: String::new(),
+            affects: Affects::Enemy,
+            ticks: EffectTicks::OwnersTurn,
+            rule: EffectRule {
+                movement_cost: 1,
+                blocks_movement: true,
+                ..Default::default()
+            },
+            per_turn: vec![EffectDef::DealDamage {
+                selector: sample_selector(),
+                amount: ValueDef::Constant(1),
+            }],
+        });
+        // Effect names are written bare, like enum variants.
+        let frozen = EffectType::new("Frozen");
+        let apply = |name: &str| {
+            ron::de::from_str::<EffectDef>(&format!(
+                "ApplyEffect(
+                    selector: (kind: Tile, cardinality: Single, selection: AllTiles, filters: []),
+                    effect: {name},
+                    duration: 1,
+                )"
+            ))
+        };
+        let apply_frozen = apply("Frozen").expect("deserialize");
+        assert!(matches!(
+            &apply_frozen,
+            EffectDef::ApplyEffect { effect, .. } if *effect == frozen
+        ));
+        assert_eq!(
+            ron::to_string(&apply_frozen)
+                .unwrap()
+                .matches("effect:Frozen,")
+                .count(),
+            1
+        );
+        // Only declared effects can be named.
+        let mut registry = EffectRegistry::default();
+        registry.insert(TileEffectDef {
+            name: frozen,
+            ..Default::default()
+        });
+        let typo = apply("Frozne").expect("deserialize");
+        assert!(registry.check(apply_frozen.effect_types()).is_ok());
+        let error = registry.check(typo.effect_types()).unwrap_err();
+        assert!(error.to_string().contains("tile effect `Frozne`"));
+    }
 }
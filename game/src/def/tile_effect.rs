@@ -0,0 +1,59 @@
+use bevy::{asset::Asset, reflect::TypePath};
+use serde::{Deserialize, Serialize};
+
+use crate::board::effect::{Affects, EffectRule, EffectTicks, EffectType};
+
+use super::{
+    condition::ConditionDef,
+    effect::EffectDef,
+    trigger::{AbilityDef, TriggerDef},
+};
+
+/// A tile effect kind loaded from a `.effect.ron` file in `assets/effects`.
+#[derive(Asset, TypePath, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TileEffectDef {
+    /// The effect type the file declares.
+    pub name: EffectType,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub display_image: String,
+    /// Which creatures `rule` and `per_turn` apply to.
+    #[serde(default)]
+    pub affects: Affects,
+    /// Whose turns the effect's duration counts down on.
+    #[serde(default)]
+    pub ticks: EffectTicks,
+    /// Stat and movement modifiers for the tile the effect lies on.
+    #[serde(default)]
+    pub rule: EffectRule,
+    /// Effects run at the start of a turn for every affected creature of the
+    /// turn player standing on the tile. The creature is the current target.
+    #[serde(default)]
+    pub per_turn: Vec<EffectDef>,
+}
+
+impl TileEffectDef {
+    /// The tile effects `per_turn` lays or checks for.
+    pub fn effect_types(&self) -> Vec<&EffectType> {
+        self.per_turn
+            .iter()
+            .flat_map(EffectDef::effect_types)
+            .collect()
+    }
+
+    /// The ability running `per_turn`, if there is anything to run.
+    pub fn per_turn_ability(&self) -> Option<AbilityDef> {
+        if self.per_turn.is_empty() {
+            return None;
+        }
+        Some(AbilityDef {
+            trigger: TriggerDef::OnTurnStart,
+            condition: ConditionDef::Always,
+            speed: Default::default(),
+            timing: Default::default(),
+            effects: self.per_turn.clone(),
+            replacement: None,
+        })
+    }
+}
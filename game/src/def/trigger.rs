@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::actions::{spell_speed::SpellSpeed, timing::ActionTiming};
+use crate::{
+    actions::{spell_speed::SpellSpeed, timing::ActionTiming},
+    board::effect::EffectType,
+};
 
-use super::{condition::ConditionDef, effect::EffectDef};
+use super::{condition::ConditionDef, effect::EffectDef, value::ValueDef};
 
 /// When an ability fires.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -13,6 +16,65 @@ pub enum TriggerDef {
     OnTurnEnd,
     /// When a trap is revealed.
     OnReveal,
+    /// When the creature is destroyed. Targets whatever destroyed it.
+    OnDeath,
+    /// When the creature takes damage. Targets the damage source.
+    OnDamaged,
+    /// When the creature moved. Targets the tile it moved to.
+    OnMove,
+    /// When the creature attacks. Targets the attacked creature or base.
+    OnAttack,
+    /// At the start of the owner's turn. Targets the owner.
+    OnTurnStart,
+    /// When the card is drawn. Targets the player who drew it.
+    OnDraw,
+    /// When the owner of the creature has to draw from an empty deck.
+    /// Targets the owner.
+    OnDeckOut,
+    /// Just before `event` happens to the card (or its owner or tile, see
+    /// [`HookDef`]). The ability's `replacement` may change or cancel it.
+    Before {
+        event: HookDef,
+        #[serde(default)]
+        once_per_turn: bool,
+    },
+    /// Right after `event` happened to the card (or its owner or tile).
+    After {
+        event: HookDef,
+        #[serde(default)]
+        once_per_turn: bool,
+    },
+}
+
+/// Atomic events an ability can hook into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HookDef {
+    // Happen to the card itself.
+    Damaged,
+    Healed,
+    Destroyed,
+    Moved,
+    StatsModified,
+    ReturnedToHand,
+    // Happen to the card's owner.
+    CardsDrawn,
+    GoldGained,
+    CardsDiscarded,
+    Milled,
+    TurnEnded,
+    // Happen to the tile the card stands on.
+    EffectApplied,
+}
+
+/// How a `Before` hook changes the event it intercepts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplacementDef {
+    /// The event does not happen.
+    Prevent,
+    /// Lowers the event's amount, e.g. damage or cards drawn.
+    Reduce(ValueDef),
+    /// Raises the event's amount.
+    Increase(ValueDef),
 }
 
 /// A triggered ability of a card: trigger + condition + effects.
@@ -27,4 +89,19 @@ pub struct AbilityDef {
     pub timing: ActionTiming,
     #[serde(default)]
     pub effects: Vec<EffectDef>,
+    /// Only used by `Before` hooks: applied to the intercepted event right
+    /// away, while `effects` go on the stack like any other ability's.
+    #[serde(default)]
+    pub replacement: Option<ReplacementDef>,
+}
+
+impl AbilityDef {
+    /// The tile effects the ability lays or checks for.
+    pub fn effect_types(&self) -> Vec<&EffectType> {
+        self.condition
+            .effect_types()
+            .into_iter()
+            .chain(self.effects.iter().flat_map(EffectDef::effect_types))
+            .collect()
+    }
 }
@@ -1,6 +1,10 @@
 use std::fmt::Display;
 
-use super::board::place_error::BoardError;
+use super::{
+    actions::spell_speed::SpellSpeed, board::place_error::BoardError,
+    card::deck_builder::DeckViolation,
+    replay::GameSnapshot,
+};
 
 #[derive(Debug)]
 pub enum GameError {
@@ -21,6 +25,19 @@ pub enum GameError {
     ActionError(&'static str),
     InputCancelled,
     NoInputReceived,
+    NoPriority,
+    TooSlow(SpellSpeed),
+    /// A deck list breaks the deck construction rules, for each reason.
+    InvalidDeck {
+        deck: String,
+        violations: Vec<DeckViolation>,
+    },
+    SaveError(String),
+    /// A replayed game ended differently from the logged one.
+    ReplayDiverged {
+        expected: Box<GameSnapshot>,
+        actual: Box<GameSnapshot>,
+    },
 }
 
 impl Display for GameError {
@@ -35,6 +52,7 @@ impl Display for GameError {
             GameError::Cast(s) => write!(f, "Cast error: {}", s),
             GameError::InsufficientGold => write!(f, "InsufficientGold"),
             GameError::LoadError(msg) => write!(f, "LoadError: {}", msg),
+            GameError::SaveError(msg) => write!(f, "SaveError: {}", msg),
             GameError::DefError(msg) => write!(f, "DefError: {}", msg),
             GameError::InvalidHandPosition(pos) => write!(f, "Invalud Hand position: {}", pos),
             GameError::Incomplete(s) => write!(f, "Inclomplete: {}", s),
@@ -43,6 +61,20 @@ impl Display for GameError {
             GameError::ActionError(s) => write!(f, "Error when performing action: {}", s),
             GameError::InputCancelled => write!(f, "Input cancelled"),
             GameError::NoInputReceived => write!(f, "No input recieved"),
+            GameError::NoPriority => write!(f, "Player does not hold priority"),
+            GameError::TooSlow(speed) => write!(f, "{:?} cards cannot be played now", speed),
+            GameError::InvalidDeck { deck, violations } => {
+                write!(f, "Deck {} is invalid:", deck)?;
+                for violation in violations {
+                    write!(f, " {};", violation)?;
+                }
+                Ok(())
+            }
+            GameError::ReplayDiverged { expected, actual } => write!(
+                f,
+                "Replay diverged: expected {:?}, got {:?}",
+                expected, actual
+            ),
         }
     }
 }
@@ -11,8 +11,8 @@ use crate::{
 
 #[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
 pub struct SpellPlayed {
-    owner: Player,
-    card: Entity,
+    pub owner: Player,
+    pub card: Entity,
 }
 
 #[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
@@ -24,9 +24,17 @@ pub struct CreaturePlayed {
 
 #[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
 pub struct TrapPlaced {
-    owner: Player,
-    card: Entity,
-    position: U16Vec2,
+    pub owner: Player,
+    pub card: Entity,
+    pub position: U16Vec2,
+}
+
+/// A face-down trap was turned over. `intruder` is the enemy creature that
+/// stepped onto the trap's tile, if that is what revealed it.
+#[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
+pub struct TrapRevealed {
+    pub trap: Entity,
+    pub intruder: Option<Entity>,
 }
 
 #[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
@@ -37,9 +45,31 @@ pub struct CardMoved {
 }
 
 #[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
-pub struct TurnEnd;
+pub struct CreatureAttacked {
+    pub attacker: Entity,
+    pub target: Entity,
+    pub damage: u16,
+}
+
+/// A creature took damage. `source` is what dealt it, if known.
+#[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
+pub struct CreatureDamaged {
+    pub creature: Entity,
+    pub source: Option<Entity>,
+    pub amount: u16,
+}
 
+/// A creature was destroyed. `killer` is what destroyed it, if known.
 #[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
+pub struct CreatureDestroyed {
+    pub creature: Entity,
+    pub killer: Option<Entity>,
+}
+
+#[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
+pub struct TurnEnd;
+
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
 pub struct EffectAdded {
     pub effect: Effect,
     pub tile: Entity,
@@ -56,7 +86,15 @@ pub struct CardsDrawn {
     pub card: Entity,
 }
 
+/// A player had to draw from an empty deck. `fatigue` is the damage they
+/// took, `None` if running out of cards lost them the game.
 #[derive(Message, Debug, Clone, PartialEq, Eq, Copy)]
+pub struct DeckedOut {
+    pub player: Entity,
+    pub fatigue: Option<u16>,
+}
+
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
 pub struct EffectRemoved {
     pub effect: EffectType,
     pub tile: Entity,
@@ -69,11 +107,16 @@ impl Plugin for GameMessagesPlugin {
         app.add_message::<SpellPlayed>()
             .add_message::<CreaturePlayed>()
             .add_message::<TrapPlaced>()
+            .add_message::<TrapRevealed>()
             .add_message::<CardMoved>()
+            .add_message::<CreatureAttacked>()
+            .add_message::<CreatureDamaged>()
+            .add_message::<CreatureDestroyed>()
             .add_message::<TurnEnd>()
             .add_message::<EffectAdded>()
             .add_message::<GoldAdded>()
             .add_message::<CardsDrawn>()
+            .add_message::<DeckedOut>()
             .add_message::<EffectRemoved>();
     }
 }
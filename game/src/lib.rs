@@ -0,0 +1,241 @@
+//! The rules of Morast as a library. [`GamePlugin`] runs a full game without
+//! a window, e.g. under `MinimalPlugins` for tests, bots and servers. The
+//! renderer and the stats display are behind the default `render` feature.
+
+use bevy::input::{ButtonInput, keyboard::KeyCode};
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+pub mod actions;
+pub mod board;
+pub mod card;
+pub mod components;
+pub mod def;
+pub mod error;
+pub mod events;
+pub mod mulligan;
+pub mod phases;
+pub mod player;
+#[cfg(feature = "render")]
+pub mod renderer;
+pub mod replay;
+pub mod rules;
+pub mod save;
+#[cfg(feature = "render")]
+pub mod stats;
+pub mod turn_controller;
+pub mod undo;
+
+use crate::{
+    actions::{ActionPlugin, targeting::systems::TargetPlugin},
+    board::BoardPlugin,
+    card::{add_cards, card_registry::CardRegistry},
+    def::loader::{CardPlugin, LoadState},
+    events::GameMessagesPlugin,
+    mulligan::begin_mulligan,
+    player::{add_player, draw_starting_cards},
+    replay::{GameLogPlugin, start_game_log},
+    rules::Ruleset,
+    save::{SavePlugin, SavedGame},
+    turn_controller::{TurnControllerPlugin, TurnPhase},
+    undo::UndoPlugin,
+};
+
+/// The source of all randomness in a game. Saved games carry its state, so
+/// a restored game draws the same numbers the original would have.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRng(pub ChaCha12Rng);
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(ChaCha12Rng::seed_from_u64(seed))
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self(ChaCha12Rng::from_os_rng())
+    }
+}
+
+/// The seed the game's randomness starts from. Games with the same seed,
+/// decks and inputs play out the same. Inserted before the game starts, it
+/// takes precedence over the ruleset's seed.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameSeed(pub u64);
+
+/// Seeds the game's randomness from the given seed, the ruleset's or a
+/// random one, and logs it so the game can be replayed.
+pub fn seed_rng(mut commands: Commands, seed: Option<Res<GameSeed>>, rules: Res<Ruleset>) {
+    let seed = seed
+        .map(|seed| seed.0)
+        .or(rules.seed)
+        .unwrap_or_else(rand::random);
+    info!("Game seed: {}", seed);
+    commands.insert_resource(GameSeed(seed));
+    commands.insert_resource(GameRng::seeded(seed));
+}
+
+/// Sets up the players and their decks, or restores a saved game, once the
+/// assets are loaded.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameSetup;
+
+/// Everything needed to play a game: asset loading, the board, turns and
+/// the action stack. Needs the `AssetPlugin` and `StatesPlugin` besides
+/// `MinimalPlugins`.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CardRegistry>()
+            .init_resource::<GameRng>()
+            // Input is read from the keyboard when there is one.
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_plugins((
+                CardPlugin,
+                GameMessagesPlugin,
+                BoardPlugin,
+                TurnControllerPlugin,
+                ActionPlugin,
+                TargetPlugin,
+                GameLogPlugin,
+                SavePlugin,
+                UndoPlugin,
+            ))
+            // The game opens with the mulligan rather than straight in a turn.
+            .insert_state(TurnPhase::Mulligan)
+            .add_systems(
+                OnEnter(LoadState::Ready),
+                (
+                    seed_rng,
+                    // A restored game cannot be replayed from its seed.
+                    start_game_log.run_if(not(resource_exists::<SavedGame>)),
+                    add_player,
+                    (add_cards, draw_starting_cards, begin_mulligan)
+                        .chain()
+                        .run_if(not(resource_exists::<SavedGame>)),
+                )
+                    .chain()
+                    .in_set(GameSetup),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        card::{card_id::CardID, deck_builder::PlayerDecks},
+        def::loader::RulesetPath,
+        player::{Deck, Hand, Player},
+        replay::{GameLog, headless_app, start_replay},
+        turn_controller::ChoiceMade,
+    };
+    use bevy::state::app::StatesPlugin;
+
+    /// A game under `MinimalPlugins` that finished loading and dealt the
+    /// opening hands.
+    fn loaded_game(seed: Option<u64>) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            StatesPlugin,
+            GamePlugin,
+        ));
+        if let Some(seed) = seed {
+            app.insert_resource(GameSeed(seed));
+        }
+
+        let mut loaded = false;
+        for _ in 0..500 {
+            app.update();
+            if app.world().resource::<State<LoadState>>().get() == &LoadState::Ready {
+                loaded = true;
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(loaded, "assets did not finish loading");
+        app.update();
+        app
+    }
+
+    fn opening_hands(app: &mut App) -> Vec<Vec<CardID>> {
+        let world = app.world_mut();
+        let mut players = world.query_filtered::<&Hand, With<Player>>();
+        let mut cards = world.query::<&CardID>();
+        players
+            .iter(world)
+            .map(|hand| hand.iter().map(|card| *cards.get(world, card).unwrap()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn a_game_runs_headless_under_minimal_plugins() {
+        let mut app = loaded_game(None);
+
+        let world = app.world_mut();
+        let mut players = world.query_filtered::<(&Hand, &Deck), With<Player>>();
+        let sizes: Vec<(usize, usize)> = players
+            .iter(world)
+            .map(|(hand, deck)| (hand.len(), deck.len()))
+            .collect();
+        assert_eq!(sizes, vec![(5, 35), (5, 35)]);
+        assert!(app.world().get_resource::<GameSeed>().is_some());
+
+        for _ in 0..2 {
+            app.world_mut().write_message(ChoiceMade::Entities(vec![]));
+            app.update();
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<State<TurnPhase>>().get(),
+            &TurnPhase::Main
+        );
+    }
+
+    #[test]
+    fn games_with_the_same_seed_deal_the_same_hands() {
+        let first = opening_hands(&mut loaded_game(Some(7)));
+        let second = opening_hands(&mut loaded_game(Some(7)));
+        let other = opening_hands(&mut loaded_game(Some(8)));
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn a_ruleset_that_does_not_load_falls_back_to_the_standard_rules() {
+        let log = GameLog::new(
+            7,
+            RulesetPath::named("shrot").0,
+            PlayerDecks::default().0,
+        );
+        let app = start_replay(&log).unwrap();
+        assert_eq!(app.world().resource::<Ruleset>(), &Ruleset::default());
+    }
+
+    #[test]
+    fn a_game_with_an_unknown_deck_ends_before_it_is_dealt() {
+        let mut app = headless_app();
+        app.insert_resource(PlayerDecks(vec!["standard".into(), "nope".into()]));
+        let mut exit = None;
+        for _ in 0..500 {
+            app.update();
+            exit = app.should_exit();
+            if exit.is_some() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(exit, Some(AppExit::error()));
+        assert_eq!(
+            app.world().resource::<State<LoadState>>().get(),
+            &LoadState::Loading
+        );
+    }
+}
@@ -1,45 +1,75 @@
+use std::path::PathBuf;
+
 use bevy::prelude::*;
 use bevy::window::{Window, WindowPlugin, WindowResolution};
-use rand::SeedableRng;
-
-mod actions;
-mod board;
-mod card;
-mod components;
-mod def;
-mod error;
-mod events;
-mod phases;
-mod player;
-mod renderer;
-mod stats;
-mod turn_controller;
 
-use crate::{
-    actions::{ActionPlugin, targeting::systems::TargetPlugin},
-    board::BoardPlugin,
-    card::{add_cards, card_registry::CardRegistry},
-    def::loader::{CardPlugin, LoadState},
-    events::GameMessagesPlugin,
-    player::{add_player, draw_starting_cards},
-    renderer::{
-        RendererPlugin, setup_creature_on_board_renderer, setup_selection_visuals,
-    },
+use game::{
+    GamePlugin, GameSeed, GameSetup,
+    card::deck_builder::PlayerDecks,
+    def::loader::{LoadState, RulesetPath},
+    renderer::{RendererPlugin, setup_creature_on_board_renderer, setup_selection_visuals},
+    replay::GameLogFile,
+    save::{SaveFile, SavedGame},
     stats::StatsPlugin,
-    turn_controller::TurnControllerPlugin,
 };
 
-#[derive(Resource)]
-pub struct GameRng(pub rand::rngs::StdRng);
+/// The command line: `game [ruleset] [deck...] [--seed <seed>] [--log <file>]
+/// [--save <file>] [--load <file>]`.
+#[derive(Default)]
+struct Args {
+    ruleset: Option<String>,
+    decks: Vec<String>,
+    seed: Option<u64>,
+    log: Option<PathBuf>,
+    save: Option<PathBuf>,
+    load: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut args = Args::default();
+        let mut positional = Vec::new();
+        let mut words = std::env::args().skip(1);
+        while let Some(word) = words.next() {
+            if word == "--seed" {
+                args.seed = words.next().and_then(|seed| seed.parse().ok());
+                if args.seed.is_none() {
+                    eprintln!("--seed needs a number, playing with a random seed");
+                }
+            } else if word == "--log" {
+                args.log = words.next().map(PathBuf::from);
+            } else if word == "--save" {
+                args.save = words.next().map(PathBuf::from);
+            } else if word == "--load" {
+                args.load = words.next().map(PathBuf::from);
+            } else {
+                positional.push(word);
+            }
+        }
+        let mut positional = positional.into_iter();
+        args.ruleset = positional.next();
+        args.decks = positional.collect();
+        args
+    }
 
-impl Default for GameRng {
-    fn default() -> Self {
-        Self(rand::rngs::StdRng::from_os_rng())
+    /// The deck lists named after the ruleset, one per seat. Seats without
+    /// one play the standard deck.
+    fn player_decks(&self) -> PlayerDecks {
+        let mut decks = PlayerDecks::default();
+        for (seat, name) in self.decks.iter().enumerate() {
+            match decks.0.get_mut(seat) {
+                Some(deck) => deck.clone_from(name),
+                None => decks.0.push(name.clone()),
+            }
+        }
+        decks
     }
 }
 
 fn main() {
-    App::new()
+    let args = Args::parse();
+    let mut app = App::new();
+    app
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 resolution: WindowResolution::new(1600, 900),
@@ -48,23 +78,40 @@ fn main() {
             }),
             ..Default::default()
         }))
-        .init_resource::<CardRegistry>()
-        .init_resource::<GameRng>()
-        .add_plugins((
-            CardPlugin,
-            GameMessagesPlugin,
-            BoardPlugin,
-            TurnControllerPlugin,
-            RendererPlugin,
-            StatsPlugin,
-            ActionPlugin,
-            TargetPlugin,
-        ))
-        .add_systems(Startup, add_player)
+        // `cargo run -- short` plays with `assets/rules/short.ruleset.ron`.
+        .insert_resource(
+            args.ruleset
+                .as_deref()
+                .map_or_else(RulesetPath::default, RulesetPath::named),
+        )
+        // `cargo run -- standard standard siege` gives the second player the
+        // `siege` deck list.
+        .insert_resource(args.player_decks())
+        .add_plugins((GamePlugin, RendererPlugin, StatsPlugin))
         .add_systems(
             OnEnter(LoadState::Ready),
-            (add_cards, draw_starting_cards, setup_creature_on_board_renderer, setup_selection_visuals)
-                .chain(),
-        )
-        .run();
+            (setup_creature_on_board_renderer, setup_selection_visuals)
+                .chain()
+                .after(GameSetup),
+        );
+    // `cargo run -- --seed 42` replays the game seeded with 42.
+    if let Some(seed) = args.seed {
+        app.insert_resource(GameSeed(seed));
+    }
+    // `cargo run -- --log game.log.ron` saves the game for `replay` on exit.
+    if let Some(log) = args.log {
+        app.insert_resource(GameLogFile(log));
+    }
+    // `cargo run -- --save game.save.ron` saves the game there on F5.
+    if let Some(save) = args.save {
+        app.insert_resource(SaveFile(save));
+    }
+    // `cargo run -- --load game.save.ron` continues a saved game.
+    if let Some(load) = args.load {
+        if let Err(e) = SavedGame::restore_file_into(&load, &mut app) {
+            eprintln!("Could not load {}: {}", load.display(), e);
+            return;
+        }
+    }
+    app.run();
 }
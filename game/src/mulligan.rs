@@ -0,0 +1,182 @@
+//! The mulligan before the first turn: one player after the other picks any
+//! cards of their opening hand to shuffle back into their deck and draws as
+//! many new ones.
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::{
+    GameRng,
+    card::{InDeck, InHand, Selected},
+    player::{Acting, Deck, Hand, Player},
+    replay::IntentWriter,
+    turn_controller::{CardClicked, ChoiceMade, TurnPhase, TurnState},
+};
+
+/// Marks a player who has not confirmed their opening hand yet.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Mulligan;
+
+/// Lets every player take a mulligan once the opening hands are drawn.
+pub fn begin_mulligan(
+    mut commands: Commands,
+    players: Query<Entity, With<Player>>,
+    mut next_state: ResMut<NextState<TurnState>>,
+) {
+    for player in &players {
+        commands.entity(player).insert(Mulligan);
+    }
+    next_state.set(TurnState::AwaitingInputs);
+}
+
+/// Clicking a card in hand marks it to be shuffled back, clicking it again
+/// keeps it. Enter confirms the marked cards.
+pub fn select_mulligan_cards(
+    mut clicks: MessageReader<CardClicked>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    hand: Single<&Hand, (With<Acting>, With<Mulligan>)>,
+    selected: Query<Entity, (With<Selected>, With<InHand>)>,
+    mut commands: Commands,
+    mut choice_made: IntentWriter<ChoiceMade>,
+) {
+    for CardClicked(card_index) in clicks.read() {
+        let Some(card) = hand.get_card(*card_index) else {
+            warn!("Invalid card index: {}", card_index);
+            continue;
+        };
+        if selected.contains(card) {
+            commands.entity(card).remove::<Selected>();
+        } else {
+            commands.entity(card).insert(Selected);
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Enter) {
+        choice_made.write(ChoiceMade::Entities(selected.iter().collect()));
+    }
+}
+
+type DecidingPlayer = (With<Acting>, With<Mulligan>);
+
+/// Shuffles the chosen cards of the deciding player back into their deck and
+/// draws as many. The first turn starts once every player has confirmed.
+pub fn resolve_mulligan(
+    mut choices: MessageReader<ChoiceMade>,
+    deciding: Query<(Entity, &Deck, &Hand), DecidingPlayer>,
+    pending: Query<(), With<Mulligan>>,
+    mut rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+    mut next_state: ResMut<NextState<TurnState>>,
+) {
+    let Some(ChoiceMade::Entities(cards)) = choices.read().last() else {
+        return;
+    };
+    let Ok((player, deck, hand)) = deciding.single() else {
+        return;
+    };
+
+    let returned: Vec<Entity> = hand.iter().filter(|card| cards.contains(card)).collect();
+    let mut library: Vec<Entity> = deck.iter().chain(returned.iter().copied()).collect();
+    library.shuffle(&mut rng.0);
+    let (drawn, rest) = library.split_at(returned.len());
+    info!(
+        "Player {} shuffles {} cards back into their deck",
+        player,
+        returned.len()
+    );
+
+    for &card in &returned {
+        commands.entity(card).remove::<(InHand, Selected)>();
+    }
+    for &card in drawn {
+        commands
+            .entity(card)
+            .remove::<InDeck>()
+            .insert(InHand { parent: player });
+    }
+    // Inserting the relationship again moves each card to the bottom, so
+    // the deck ends up in shuffled order.
+    for &card in rest {
+        commands.entity(card).insert(InDeck { parent: player });
+    }
+    commands.entity(player).remove::<Mulligan>();
+
+    if pending.iter().count() <= 1 {
+        info!("All players kept their hands");
+        next_phase.set(TurnPhase::Main);
+        next_state.set(TurnState::Idle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::ActionPlugin,
+        events::GameMessagesPlugin,
+        player::{PlayerBundle, TurnPlayer},
+        turn_controller::TurnControllerPlugin,
+    };
+    use bevy::{ecs::system::RunSystemOnce, state::app::StatesPlugin};
+
+    #[test]
+    fn the_first_turn_starts_once_both_players_kept_their_hands() {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .insert_state(TurnPhase::Mulligan)
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let first = app
+            .world_mut()
+            .spawn((Player { number: 0 }, PlayerBundle::default(), TurnPlayer))
+            .id();
+        let second = app
+            .world_mut()
+            .spawn((Player { number: 1 }, PlayerBundle::default()))
+            .id();
+        let hand: Vec<Entity> = (0..3)
+            .map(|_| app.world_mut().spawn(InHand { parent: first }).id())
+            .collect();
+        for _ in 0..5 {
+            app.world_mut().spawn(InDeck { parent: first });
+        }
+        app.world_mut().run_system_once(begin_mulligan).unwrap();
+        app.update();
+
+        app.world_mut()
+            .write_message(ChoiceMade::Entities(vec![hand[0], hand[1]]));
+        app.update();
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<Hand>(first).unwrap().len(), 3);
+        assert_eq!(world.get::<Deck>(first).unwrap().len(), 5);
+        assert!(world.get::<InHand>(hand[2]).is_some());
+        assert!(world.get::<Acting>(second).is_some());
+        assert_eq!(
+            world.resource::<State<TurnPhase>>().get(),
+            &TurnPhase::Mulligan
+        );
+
+        app.world_mut().write_message(ChoiceMade::Entities(vec![]));
+        app.update();
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<State<TurnPhase>>().get(),
+            &TurnPhase::Main
+        );
+        assert_eq!(
+            app.world().resource::<State<TurnState>>().get(),
+            &TurnState::Idle
+        );
+        assert!(app.world().get::<Acting>(first).is_some());
+    }
+}
@@ -1,14 +1,24 @@
-use bevy::ecs::{
-    bundle::Bundle,
-    component::Component,
-    entity::Entity,
-    relationship::RelationshipTarget,
-    system::{Commands, Query},
+use bevy::{
+    ecs::{
+        bundle::Bundle,
+        component::Component,
+        entity::Entity,
+        query::With,
+        relationship::RelationshipTarget,
+        system::{Commands, Query, Res},
+    },
+    log::info,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::card::{InDeck, InGraveyard, InHand};
+use crate::{
+    actions::{AddGold, DiscardCards, DrawCards, hooks::TriggerHookedExt},
+    card::{InDeck, InGraveyard, InHand},
+    rules::Ruleset,
+    turn_controller::TurnCounter,
+};
 
-#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Player {
     pub number: u8,
 }
@@ -19,6 +29,9 @@ pub struct PlayerResources {
     pub health: u16,
     pub max_health: u16,
     pub gold: u16,
+    /// Fatigue damage the player took the last time they drew from an
+    /// empty deck.
+    pub fatigue: u16,
 }
 
 impl Default for PlayerResources {
@@ -27,6 +40,7 @@ impl Default for PlayerResources {
             health: 10,
             max_health: 10,
             gold: 10,
+            fatigue: 0,
         }
     }
 }
@@ -35,6 +49,11 @@ impl Default for PlayerResources {
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TurnPlayer;
 
+/// The player making the next decision: the turn player, or whoever holds
+/// priority while abilities wait on the effect stack.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Acting;
+
 #[derive(Component, Debug, Clone, PartialEq, Eq, Default)]
 #[relationship_target(relationship = InDeck)]
 pub struct Deck(Vec<Entity>);
@@ -61,14 +80,35 @@ pub struct PlayerBundle {
     graveyard: Graveyard,
 }
 
-pub fn add_player(mut commands: Commands) {
-    commands.spawn((Player { number: 0 }, PlayerBundle::default(), TurnPlayer));
-    commands.spawn((Player { number: 1 }, PlayerBundle::default()));
+impl PlayerBundle {
+    fn new(rules: &Ruleset) -> Self {
+        Self {
+            resources: PlayerResources {
+                health: rules.player_health,
+                max_health: rules.player_health,
+                gold: rules.starting_gold,
+                fatigue: 0,
+            },
+            ..Default::default()
+        }
+    }
 }
 
-pub fn draw_starting_cards(mut players: Query<(&mut Deck, Entity)>, mut commands: Commands) {
+/// How many players sit at a game.
+pub const SEATS: u8 = 2;
+
+pub fn add_player(mut commands: Commands, rules: Res<Ruleset>) {
+    commands.spawn((Player { number: 0 }, PlayerBundle::new(&rules), TurnPlayer));
+    commands.spawn((Player { number: 1 }, PlayerBundle::new(&rules)));
+}
+
+pub fn draw_starting_cards(
+    mut players: Query<(&mut Deck, Entity)>,
+    mut commands: Commands,
+    rules: Res<Ruleset>,
+) {
     for (deck, player) in &mut players {
-        for card in deck.iter().take(5) {
+        for card in deck.iter().take(rules.starting_hand as usize) {
             commands
                 .entity(card)
                 .remove::<InDeck>()
@@ -76,3 +116,46 @@ pub fn draw_starting_cards(mut players: Query<(&mut Deck, Entity)>, mut commands
         }
     }
 }
+
+/// The turn player draws their cards for the turn and collects their income.
+pub fn draw_and_collect_income(
+    mut commands: Commands,
+    turn_player: Query<Entity, With<TurnPlayer>>,
+    players: Query<(), With<Player>>,
+    counter: Res<TurnCounter>,
+    rules: Res<Ruleset>,
+) {
+    let Ok(player) = turn_player.single() else {
+        return;
+    };
+    let seats = players.iter().count().max(1) as u32;
+    let round = counter.0.saturating_sub(1) / seats + 1;
+    let income = rules.income.for_round(round);
+    info!(
+        "Player {} draws {} and collects {} gold",
+        player, rules.cards_per_turn, income
+    );
+
+    commands.trigger_hooked(DrawCards::new(rules.cards_per_turn, player));
+    commands.trigger_hooked(AddGold::new(income, player));
+}
+
+/// The turn player discards down to the maximum hand size, losing the cards
+/// drawn last.
+pub fn discard_to_hand_limit(
+    mut commands: Commands,
+    turn_player: Query<(Entity, &Hand), With<TurnPlayer>>,
+    rules: Res<Ruleset>,
+) {
+    let Ok((player, hand)) = turn_player.single() else {
+        return;
+    };
+    let excess = (hand.len() as u16).saturating_sub(rules.max_hand_size);
+    if excess > 0 {
+        info!(
+            "Player {} discards {} down to the hand limit",
+            player, excess
+        );
+        commands.trigger_hooked(DiscardCards::new(excess, player));
+    }
+}
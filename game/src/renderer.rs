@@ -1,7 +1,7 @@
 use bevy::{
     app::{Plugin, Startup, Update},
     asset::AssetServer,
-    camera::Camera2d,
+    camera::{Camera2d, visibility::Visibility},
     color::{Color, Srgba},
     ecs::{
         component::Component,
@@ -12,12 +12,13 @@ use bevy::{
         message::MessageWriter,
         name::Name,
         observer::On,
-        query::{Added, Changed, With, Without},
+        query::{Added, Changed, Has, Or, With, Without},
         relationship::{RelatedSpawnerCommands, RelationshipTarget},
         schedule::{
             common_conditions::resource_changed,
             IntoScheduleConfigs,
         },
+        change_detection::DetectChangesMut,
         system::{Commands, Query, Res, Single},
     },
     log::{info, warn},
@@ -27,29 +28,34 @@ use bevy::{
         events::{Pointer, Release},
     },
     sprite::{Anchor, Sprite, Text2d},
+    state::state::OnEnter,
     text::{TextColor, TextFont},
     transform::components::{GlobalTransform, Transform},
 };
 
 use crate::{
     board::{
-        Board, BoardRes,
-        effect::EffectType,
+        Board, BoardRes, PlayerBase,
+        effect::{EffectRegistry, EffectType, Tiles},
         movement::reachable_tiles,
         tile::{EffectsOnTile, Occupant, Position, Tile},
     },
     card::{
         Cost, CreatureCard, CurrentAttack, CurrentDefense, CurrentMovementPoints, InHand, OnBoard,
+        SetOnTile,
+        abilities::CardAbilities,
         card_id::CardID,
         creature::{BaseMovementPoints, MovementPattern},
     },
-    components::Health,
-    player::{Hand, TurnPlayer},
+    components::{Health, Owner},
+    def::loader::LoadState,
+    player::{Acting, Hand, Player},
     renderer::layout::{
         LayoutConfig, ScreenLayout, compute_screen_layout_on_resize,
-        compute_screen_layout_startup,
+        compute_screen_layout_on_ruleset, compute_screen_layout_startup,
     },
-    turn_controller::{CardClicked, EndTurnPressed, Origin},
+    replay::IntentWriter,
+    turn_controller::{BoardClicked, CardClicked, EndTurnPressed, Origin},
 };
 
 pub mod layout;
@@ -65,15 +71,17 @@ impl Plugin for RendererPlugin {
                 (
                     setup_camera,
                     compute_screen_layout_startup,
-                    render_board
-                        .after(compute_screen_layout_startup)
-                        .after(BoardRes::setup_board),
-                    render_tiles
-                        .after(compute_screen_layout_startup)
-                        .after(BoardRes::setup_board),
                     spawn_end_turn_button.after(compute_screen_layout_startup),
                 ),
             )
+            .add_systems(
+                OnEnter(LoadState::Ready),
+                (
+                    compute_screen_layout_on_ruleset,
+                    (render_board, render_tiles).after(BoardRes::setup_board),
+                )
+                    .chain(),
+            )
             .add_systems(
                 Update,
                 (
@@ -83,10 +91,15 @@ impl Plugin for RendererPlugin {
                     apply_end_turn_layout.run_if(resource_changed::<ScreenLayout>),
                     apply_creature_layout.run_if(resource_changed::<ScreenLayout>),
                     spawn_hand_card_visuals,
+                    sync_hand_visibility,
                     position_hand_cards,
                     render_effects_on_tile,
+                    render_player_bases,
+                    render_set_traps,
+                    sync_trap_visibility,
                 ),
-            );
+            )
+            .add_observer(on_trap_left_tile);
     }
 }
 
@@ -100,10 +113,16 @@ fn setup_camera(mut commands: Commands) {
 
 pub fn setup_creature_on_board_renderer(
     mut commands: Commands,
-    creatures: Query<Entity, With<CardID>>,
+    creatures: Query<(Entity, Option<&OnBoard>), With<CardID>>,
+    asset_server: Res<AssetServer>,
+    layout: Res<ScreenLayout>,
 ) {
-    for creature in creatures {
+    for (creature, on_board) in creatures {
         commands.entity(creature).observe(render_creature_on_board);
+        // Creatures of a restored game are on the board from the start.
+        if let Some(on_board) = on_board {
+            draw_creature(&mut commands, creature, on_board.position, &asset_server, &layout);
+        }
     }
 }
 
@@ -163,20 +182,30 @@ pub fn render_tiles(
             Anchor::TOP_LEFT,
             Pickable::default(),
         ));
+        commands.entity(entity).observe(on_tile_clicked);
     }
 }
 
 pub fn render_effects_on_tile(
     tiles_with_effect: Query<&EffectsOnTile, With<Tile>>,
+    effect_types: Query<&EffectType>,
+    registry: Res<EffectRegistry>,
     mut commands: Commands,
     layout: Res<ScreenLayout>,
     asset_server: Res<AssetServer>,
 ) {
     for tile_effects in tiles_with_effect {
         for effect in tile_effects.iter() {
+            let image = effect_types
+                .get(effect)
+                .ok()
+                .and_then(|effect_type| registry.get(effect_type))
+                .map(|def| def.display_image.clone())
+                .filter(|image| !image.is_empty())
+                .unwrap_or_else(|| "effect.png".into());
             commands.entity(effect).insert((
                 Sprite {
-                    image: asset_server.load("effect.png"),
+                    image: asset_server.load(image),
                     custom_size: Some(layout.tile_size * Vec2::ONE),
                     ..Default::default()
                 },
@@ -202,8 +231,19 @@ pub fn render_creature_on_board(
     let (tile_entity, &Position(pos), _global_transform) = tiles.get(on_board.position)?;
 
     info!("Rendering creature on board at position {}", pos);
+    draw_creature(&mut commands, event.entity, tile_entity, &asset_server, &layout);
 
-    commands.entity(event.entity).insert((
+    Ok(())
+}
+
+fn draw_creature(
+    commands: &mut Commands,
+    creature: Entity,
+    tile: Entity,
+    asset_server: &AssetServer,
+    layout: &ScreenLayout,
+) {
+    commands.entity(creature).insert((
         Sprite {
             image: asset_server.load("knight.png"),
             custom_size: Some(layout.tile_size * Vec2::ONE),
@@ -214,10 +254,83 @@ pub fn render_creature_on_board(
             -layout.tile_size / 2.0,
             2.0,
         ),
-        ChildOf(tile_entity),
+        ChildOf(tile),
     ));
+}
 
-    Ok(())
+/// Draws a player's base on its tile once it has been placed on the board.
+pub fn render_player_bases(
+    bases: Query<(Entity, &OnBoard, &Owner), Added<PlayerBase>>,
+    players: Query<&Player>,
+    mut commands: Commands,
+    layout: Res<ScreenLayout>,
+) {
+    for (base, on_board, owner) in &bases {
+        let color = match players.get(owner.0).map(|player| player.number) {
+            Ok(0) => Color::srgb(0.2, 0.4, 0.8),
+            _ => Color::srgb(0.8, 0.25, 0.2),
+        };
+
+        commands.entity(base).insert((
+            Sprite {
+                color,
+                custom_size: Some(layout.tile_size * Vec2::ONE),
+                ..Default::default()
+            },
+            Transform::from_xyz(
+                layout.tile_size / 2.0,
+                -layout.tile_size / 2.0,
+                2.0,
+            ),
+            ChildOf(on_board.position),
+        ));
+    }
+}
+
+/// Draws traps face-down on the tile they were set on.
+pub fn render_set_traps(
+    traps: Query<(Entity, &SetOnTile), Added<SetOnTile>>,
+    mut commands: Commands,
+    layout: Res<ScreenLayout>,
+) {
+    for (trap, set_on) in &traps {
+        commands.entity(trap).insert((
+            Sprite {
+                color: Color::srgb(0.35, 0.15, 0.45),
+                custom_size: Some(layout.tile_size * 0.6 * Vec2::ONE),
+                ..Default::default()
+            },
+            Transform::from_xyz(
+                layout.tile_size / 2.0,
+                -layout.tile_size / 2.0,
+                1.5,
+            ),
+            ChildOf(set_on.tile),
+        ));
+    }
+}
+
+/// Face-down traps are only shown to their owner, i.e. while the owner is
+/// the acting player.
+fn sync_trap_visibility(
+    mut traps: Query<(&Owner, &mut Visibility), With<SetOnTile>>,
+    acting: Query<Entity, With<Acting>>,
+) {
+    let viewer = acting.single().ok();
+    for (owner, mut visibility) in &mut traps {
+        let wanted = if Some(owner.0) == viewer {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(wanted);
+    }
+}
+
+fn on_trap_left_tile(trigger: On<Remove, SetOnTile>, mut commands: Commands) {
+    if let Ok(mut entity_commands) = commands.get_entity(trigger.entity) {
+        entity_commands.remove::<(Sprite, Transform, ChildOf)>();
+    }
 }
 
 // ============================================================================
@@ -241,8 +354,11 @@ struct SelectedCreatureStats;
 #[derive(Component)]
 struct HandCardLabel;
 
+/// The acting player's hand, whenever it changes or play passes to them.
+type ActingHandChanged = (With<Acting>, Or<(Changed<Hand>, Added<Acting>)>);
+
 fn spawn_hand_card_visuals(
-    hand: Single<&Hand, (With<TurnPlayer>, Changed<Hand>)>,
+    hand: Single<&Hand, ActingHandChanged>,
     cards: Query<(&Name, &Cost), (With<InHand>, Without<HandCardVisual>)>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -280,6 +396,30 @@ fn spawn_hand_card_visuals(
     }
 }
 
+/// Shows the hand of the acting player and hides every other hand whenever
+/// play passes to someone else.
+fn sync_hand_visibility(
+    changed: Query<(), Added<Acting>>,
+    hands: Query<(&Hand, Has<Acting>)>,
+    mut visibility: Query<&mut Visibility, With<HandCardVisual>>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+
+    for (hand, is_acting) in &hands {
+        for card in hand.iter() {
+            if let Ok(mut vis) = visibility.get_mut(card) {
+                *vis = if is_acting {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
+
 // ============================================================================
 // End Turn Button
 // ============================================================================
@@ -367,7 +507,7 @@ fn apply_creature_layout(
 }
 
 fn position_hand_cards(
-    hand: Single<&Hand, With<TurnPlayer>>,
+    hand: Single<&Hand, With<Acting>>,
     layout: Res<ScreenLayout>,
     mut transforms: Query<&mut Transform>,
 ) {
@@ -427,10 +567,25 @@ fn spawn_card_ui(
 // Event Observers
 // ============================================================================
 
+fn on_tile_clicked(
+    click: On<Pointer<Release>>,
+    mut board_clicked: MessageWriter<BoardClicked>,
+    tiles: Query<&Position, With<Tile>>,
+) {
+    info!("Tile clicked");
+    let &Position(position) = tiles
+        .get(click.entity)
+        .expect("Clicked thing is somehow not a tile");
+    board_clicked.write(BoardClicked {
+        position,
+        entity: click.entity,
+    });
+}
+
 fn on_card_clicked(
     click: On<Pointer<Release>>,
     mut event_writer: MessageWriter<CardClicked>,
-    hands: Query<&Hand, With<TurnPlayer>>,
+    hands: Query<&Hand, With<Acting>>,
 ) {
     let Ok(hand) = hands.single() else {
         return;
@@ -447,7 +602,7 @@ fn on_card_clicked(
 
 fn on_end_turn_clicked(
     _click: On<Pointer<Release>>,
-    mut writer: MessageWriter<EndTurnPressed>,
+    mut writer: IntentWriter<EndTurnPressed>,
 ) {
     info!("End turn button clicked");
     writer.write(EndTurnPressed);
@@ -492,12 +647,13 @@ fn on_figure_selected(
             &CurrentDefense,
             &Health,
             &BaseMovementPoints,
+            &Owner,
+            Option<&CardAbilities>,
         ),
         With<CreatureCard>,
     >,
-    tiles: Query<&Position>,
+    tiles: Tiles,
     occupied: Query<(&Position, &Occupant)>,
-    effects: Query<(&EffectType, &ChildOf)>,
     board: Res<BoardRes>,
     layout: Res<ScreenLayout>,
     asset_server: Res<AssetServer>,
@@ -513,21 +669,26 @@ fn on_figure_selected(
         defense,
         health,
         base_movement,
+        owner,
+        abilities,
     )) = creatures.get(event.entity)
     else {
         return;
     };
 
-    let Ok(&Position(current_pos)) = tiles.get(on_board.position) else {
+    let Ok(current_pos) = tiles.position(on_board.position) else {
         warn!("Could not resolve position for selected creature");
         return;
     };
 
     let occupied_positions: Vec<U16Vec2> = occupied.iter().map(|(pos, _)| pos.0).collect();
 
-    let tile_has_slow = effects
-        .iter()
-        .any(|(effect, co)| co.0 == on_board.position && *effect == EffectType::Slow);
+    let tile_rule = |pos: U16Vec2| {
+        board
+            .get_tile(&pos)
+            .map(|tile| tiles.rule(tile, owner.0))
+            .unwrap_or_default()
+    };
 
     let reachable = reachable_tiles(
         &board,
@@ -535,7 +696,8 @@ fn on_figure_selected(
         movement_points.0,
         pattern,
         &occupied_positions,
-        tile_has_slow,
+        tile_rule,
+        &abilities.cloned().unwrap_or_default(),
     );
 
     // Overlay a highlight on every reachable tile.
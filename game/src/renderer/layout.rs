@@ -8,7 +8,7 @@ use bevy::{
     window::{Window, WindowResized},
 };
 
-use crate::board::BoardRes;
+use crate::rules::Ruleset;
 
 /// Tunable layout parameters. Changing these and re-running recompute
 /// (or resizing the window) re-derives the whole screen layout.
@@ -103,8 +103,9 @@ impl Default for ScreenLayout {
 }
 
 impl ScreenLayout {
-    /// Recomputes all regions and derived sizes from the current window size.
-    pub fn recompute(&mut self, config: &LayoutConfig, window: Vec2) {
+    /// Recomputes all regions and derived sizes from the current window size
+    /// and the board size in tiles.
+    pub fn recompute(&mut self, config: &LayoutConfig, window: Vec2, board: U16Vec2) {
         self.window = window;
         self.card_size = Vec2::new(config.card_width, config.card_height);
         self.card_gap = config.card_gap;
@@ -134,7 +135,7 @@ impl ScreenLayout {
         );
 
         // Largest tile size that still fits the whole grid inside the board region.
-        let grid = Vec2::new(BoardRes::XSIZE as f32, BoardRes::YSIZE as f32);
+        let grid = board.as_vec2();
         self.tile_size = (self.board.width / grid.x)
             .min(self.board.height / grid.y)
             .max(1.0);
@@ -202,8 +203,23 @@ pub fn compute_screen_layout_startup(
     mut layout: ResMut<ScreenLayout>,
     config: Res<LayoutConfig>,
     window: Single<&Window>,
+    rules: Res<Ruleset>,
 ) {
-    layout.recompute(&config, Vec2::new(window.width(), window.height()));
+    layout.recompute(
+        &config,
+        Vec2::new(window.width(), window.height()),
+        rules.board_size(),
+    );
+}
+
+/// Recomputes the layout for the board size of the loaded ruleset.
+pub fn compute_screen_layout_on_ruleset(
+    mut layout: ResMut<ScreenLayout>,
+    config: Res<LayoutConfig>,
+    rules: Res<Ruleset>,
+) {
+    let window = layout.window;
+    layout.recompute(&config, window, rules.board_size());
 }
 
 /// Recomputes the layout whenever the window is resized.
@@ -211,9 +227,10 @@ pub fn compute_screen_layout_on_resize(
     mut layout: ResMut<ScreenLayout>,
     config: Res<LayoutConfig>,
     mut events: MessageReader<WindowResized>,
+    rules: Res<Ruleset>,
 ) {
     for e in events.read() {
-        layout.recompute(&config, Vec2::new(e.width, e.height));
+        layout.recompute(&config, Vec2::new(e.width, e.height), rules.board_size());
     }
 }
 
@@ -224,7 +241,11 @@ mod tests {
     fn default_layout() -> ScreenLayout {
         let config = LayoutConfig::default();
         let mut layout = ScreenLayout::default();
-        layout.recompute(&config, Vec2::new(1600.0, 900.0));
+        layout.recompute(
+            &config,
+            Vec2::new(1600.0, 900.0),
+            Ruleset::default().board_size(),
+        );
         layout
     }
 
@@ -0,0 +1,695 @@
+//! A log of every player intent in a game and a headless replay of it.
+//! Together with the seed, the ruleset and the deck lists, the intents play
+//! the game out again exactly. A saved log reproduces a bug, and replaying
+//! old logs shows whether a card change alters how games go.
+
+use std::{collections::VecDeque, path::PathBuf, time::Duration};
+
+use bevy::{
+    ecs::{message::Messages, system::SystemParam},
+    math::U16Vec2,
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GamePlugin, GameSeed,
+    actions::stack::PassPriority,
+    board::{PlayerBase, combat::AttackRequest, movement::MoveRequest, tile::Position},
+    card::{
+        CreatureCard, CurrentAttack, CurrentDefense, OnBoard, deck_builder::PlayerDecks,
+        in_play_id::InPlayID,
+    },
+    components::{Health, Owner},
+    def::loader::{LoadState, RulesetPath},
+    error::GameError,
+    player::{Deck, Graveyard, Hand, Player, PlayerResources},
+    turn_controller::{CardPlayRequested, ChoiceMade, EndTurnPressed, TurnCounter},
+    undo::UndoPressed,
+};
+
+/// Updates every replayed intent gets to take effect before the next one.
+const SETTLE_FRAMES: usize = 10;
+
+/// A game object as the log refers to it. Entities differ between runs, so
+/// cards go by the number they were dealt with, tiles by their position and
+/// players and their bases by seat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoggedEntity {
+    Card(InPlayID),
+    Tile(U16Vec2),
+    Player(u8),
+    Base(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoggedChoice {
+    Option(usize),
+    Entities(Vec<LoggedEntity>),
+    Cancelled,
+}
+
+/// Something a player asked the game to do.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Intent {
+    PlayCard {
+        card: LoggedEntity,
+        position: U16Vec2,
+    },
+    Move {
+        creature: LoggedEntity,
+        from: U16Vec2,
+        to: U16Vec2,
+    },
+    Attack {
+        attacker: LoggedEntity,
+        target: LoggedEntity,
+    },
+    Choice(LoggedChoice),
+    PassPriority {
+        seat: u8,
+    },
+    EndTurn,
+    /// Takes back the last move.
+    Undo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub health: u16,
+    pub gold: u16,
+    pub base_health: Option<u16>,
+    /// Names of the cards in hand, in order.
+    pub hand: Vec<String>,
+    pub deck: usize,
+    pub graveyard: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreatureSnapshot {
+    pub name: String,
+    pub owner: u8,
+    pub position: U16Vec2,
+    pub attack: u16,
+    pub health: u16,
+}
+
+/// How a game stands: the players by seat and the creatures on the board
+/// by position.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub turn: u32,
+    pub players: Vec<PlayerSnapshot>,
+    pub creatures: Vec<CreatureSnapshot>,
+}
+
+impl GameSnapshot {
+    pub fn take(world: &mut World) -> Self {
+        let mut players = world.query::<(Entity, &Player, &PlayerResources, &Hand, &Deck, &Graveyard)>();
+        let mut bases = world.query_filtered::<(&Owner, &Health), With<PlayerBase>>();
+        let mut creatures = world.query_filtered::<
+            (&Name, &Owner, &OnBoard, &CurrentAttack, &CurrentDefense),
+            With<CreatureCard>,
+        >();
+        let world = &*world;
+        let name = |card: Entity| {
+            world
+                .get::<Name>(card)
+                .map(|name| name.as_str().to_string())
+                .unwrap_or_default()
+        };
+        let seat = |player: Entity| world.get::<Player>(player).map_or(u8::MAX, |p| p.number);
+
+        let mut seats: Vec<_> = players
+            .iter(world)
+            .map(|(entity, player, resources, hand, deck, graveyard)| {
+                let base_health = bases
+                    .iter(world)
+                    .find(|(owner, _)| owner.0 == entity)
+                    .map(|(_, health)| health.value());
+                let snapshot = PlayerSnapshot {
+                    health: resources.health,
+                    gold: resources.gold,
+                    base_health,
+                    hand: hand.iter().map(name).collect(),
+                    deck: deck.len(),
+                    graveyard: graveyard.len(),
+                };
+                (player.number, snapshot)
+            })
+            .collect();
+        seats.sort_by_key(|(number, _)| *number);
+
+        let mut creatures: Vec<CreatureSnapshot> = creatures
+            .iter(world)
+            .filter_map(|(creature_name, owner, on_board, attack, defense)| {
+                Some(CreatureSnapshot {
+                    name: creature_name.as_str().to_string(),
+                    owner: seat(owner.0),
+                    position: world.get::<Position>(on_board.position)?.0,
+                    attack: attack.0,
+                    health: defense.0,
+                })
+            })
+            .collect();
+        creatures.sort_by_key(|creature| (creature.position.x, creature.position.y));
+
+        Self {
+            turn: world.get_resource::<TurnCounter>().map_or(0, |turn| turn.0),
+            players: seats.into_iter().map(|(_, snapshot)| snapshot).collect(),
+            creatures,
+        }
+    }
+}
+
+/// Every intent of the game so far, together with what it takes to set the
+/// game up again.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameLog {
+    pub seed: u64,
+    /// Path of the ruleset, relative to `assets`.
+    pub ruleset: String,
+    /// The deck list of every seat.
+    pub decks: Vec<String>,
+    pub intents: Vec<Intent>,
+    /// How the game stood when the log was saved.
+    #[serde(default)]
+    pub final_state: Option<GameSnapshot>,
+}
+
+impl GameLog {
+    pub fn new(seed: u64, ruleset: String, decks: Vec<String>) -> Self {
+        Self {
+            seed,
+            ruleset,
+            decks,
+            intents: Vec::new(),
+            final_state: None,
+        }
+    }
+}
+
+/// Where the game log is saved as the game goes on.
+#[derive(Resource, Debug, Clone)]
+pub struct GameLogFile(pub PathBuf);
+
+/// Starts logging the game once its seed is known.
+pub fn start_game_log(
+    mut commands: Commands,
+    seed: Res<GameSeed>,
+    ruleset: Res<RulesetPath>,
+    decks: Res<PlayerDecks>,
+) {
+    commands.insert_resource(GameLog::new(seed.0, ruleset.0.clone(), decks.0.clone()));
+}
+
+/// The kinds of message players change the game with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntentKind {
+    PlayCard,
+    Move,
+    Attack,
+    Choice,
+    PassPriority,
+    EndTurn,
+    Undo,
+}
+
+/// A message players change the game with.
+pub trait IntentMessage: Message {
+    const KIND: IntentKind;
+}
+
+impl IntentMessage for CardPlayRequested {
+    const KIND: IntentKind = IntentKind::PlayCard;
+}
+
+impl IntentMessage for MoveRequest {
+    const KIND: IntentKind = IntentKind::Move;
+}
+
+impl IntentMessage for AttackRequest {
+    const KIND: IntentKind = IntentKind::Attack;
+}
+
+impl IntentMessage for ChoiceMade {
+    const KIND: IntentKind = IntentKind::Choice;
+}
+
+impl IntentMessage for PassPriority {
+    const KIND: IntentKind = IntentKind::PassPriority;
+}
+
+impl IntentMessage for EndTurnPressed {
+    const KIND: IntentKind = IntentKind::EndTurn;
+}
+
+impl IntentMessage for UndoPressed {
+    const KIND: IntentKind = IntentKind::Undo;
+}
+
+/// Sent with every intent message, so the log keeps the intents of a frame
+/// in the order they were sent rather than grouped by kind.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntentSent(pub IntentKind);
+
+/// Sends intent messages of one kind and notes the order they were sent in.
+#[derive(SystemParam)]
+pub struct IntentWriter<'w, M: IntentMessage> {
+    messages: MessageWriter<'w, M>,
+    sent: MessageWriter<'w, IntentSent>,
+}
+
+impl<M: IntentMessage> IntentWriter<'_, M> {
+    pub fn write(&mut self, message: M) {
+        self.sent.write(IntentSent(M::KIND));
+        self.messages.write(message);
+    }
+}
+
+/// Sends intent messages straight into the world, e.g. from a replay or a
+/// test, in the order the log keeps them.
+pub trait WriteIntentExt {
+    fn write_intent<M: IntentMessage>(&mut self, message: M);
+}
+
+impl WriteIntentExt for World {
+    fn write_intent<M: IntentMessage>(&mut self, message: M) {
+        self.write_message(IntentSent(M::KIND));
+        self.write_message(message);
+    }
+}
+
+/// The messages players change the game with.
+#[derive(SystemParam)]
+pub struct IntentMessages<'w, 's> {
+    sent: MessageReader<'w, 's, IntentSent>,
+    plays: MessageReader<'w, 's, CardPlayRequested>,
+    moves: MessageReader<'w, 's, MoveRequest>,
+    attacks: MessageReader<'w, 's, AttackRequest>,
+    choices: MessageReader<'w, 's, ChoiceMade>,
+    passes: MessageReader<'w, 's, PassPriority>,
+    end_turns: MessageReader<'w, 's, EndTurnPressed>,
+    undos: MessageReader<'w, 's, UndoPressed>,
+}
+
+/// Looks up how the log refers to an entity.
+#[derive(SystemParam)]
+pub struct LoggedEntities<'w, 's> {
+    cards: Query<'w, 's, &'static InPlayID>,
+    tiles: Query<'w, 's, &'static Position>,
+    players: Query<'w, 's, &'static Player>,
+    bases: Query<'w, 's, &'static Owner, With<PlayerBase>>,
+}
+
+impl LoggedEntities<'_, '_> {
+    fn get(&self, entity: Entity) -> Option<LoggedEntity> {
+        if let Ok(id) = self.cards.get(entity) {
+            return Some(LoggedEntity::Card(*id));
+        }
+        if let Ok(position) = self.tiles.get(entity) {
+            return Some(LoggedEntity::Tile(position.0));
+        }
+        if let Ok(player) = self.players.get(entity) {
+            return Some(LoggedEntity::Player(player.number));
+        }
+        let owner = self.bases.get(entity).ok()?;
+        Some(LoggedEntity::Base(self.players.get(owner.0).ok()?.number))
+    }
+
+    fn choice(&self, choice: &ChoiceMade) -> Option<LoggedChoice> {
+        Some(match choice {
+            ChoiceMade::Option(index) => LoggedChoice::Option(*index),
+            ChoiceMade::Entities(entities) => LoggedChoice::Entities(
+                entities
+                    .iter()
+                    .map(|&entity| self.get(entity))
+                    .collect::<Option<_>>()?,
+            ),
+            ChoiceMade::Cancelled => LoggedChoice::Cancelled,
+        })
+    }
+}
+
+/// Adds the intents of this frame to the log in the order they were sent.
+/// Runs after `Update`, where every intent is sent. Intents sent without
+/// [`IntentWriter`] come last, by kind.
+fn record_intents(
+    mut messages: IntentMessages,
+    entities: LoggedEntities,
+    mut log: ResMut<GameLog>,
+) {
+    let mut plays: VecDeque<_> = messages
+        .plays
+        .read()
+        .map(|play| {
+            entities.get(play.card).map(|card| Intent::PlayCard {
+                card,
+                position: play.position,
+            })
+        })
+        .collect();
+    let mut moves: VecDeque<_> = messages
+        .moves
+        .read()
+        .map(|request| {
+            entities.get(request.entity).map(|creature| Intent::Move {
+                creature,
+                from: request.from,
+                to: request.to,
+            })
+        })
+        .collect();
+    let mut attacks: VecDeque<_> = messages
+        .attacks
+        .read()
+        .map(|request| {
+            entities
+                .get(request.attacker)
+                .zip(entities.get(request.target))
+                .map(|(attacker, target)| Intent::Attack { attacker, target })
+        })
+        .collect();
+    let mut choices: VecDeque<_> = messages
+        .choices
+        .read()
+        .map(|choice| entities.choice(choice).map(Intent::Choice))
+        .collect();
+    let mut passes: VecDeque<_> = messages
+        .passes
+        .read()
+        .map(|pass| {
+            entities
+                .players
+                .get(pass.player)
+                .ok()
+                .map(|player| Intent::PassPriority {
+                    seat: player.number,
+                })
+        })
+        .collect();
+    let mut end_turns: VecDeque<_> = messages
+        .end_turns
+        .read()
+        .map(|_| Some(Intent::EndTurn))
+        .collect();
+    let mut undos: VecDeque<_> = messages.undos.read().map(|_| Some(Intent::Undo)).collect();
+
+    let mut intents = Vec::new();
+    for IntentSent(kind) in messages.sent.read() {
+        let queue = match kind {
+            IntentKind::PlayCard => &mut plays,
+            IntentKind::Move => &mut moves,
+            IntentKind::Attack => &mut attacks,
+            IntentKind::Choice => &mut choices,
+            IntentKind::PassPriority => &mut passes,
+            IntentKind::EndTurn => &mut end_turns,
+            IntentKind::Undo => &mut undos,
+        };
+        intents.extend(queue.pop_front());
+    }
+    for queue in [plays, moves, attacks, choices, passes, end_turns, undos] {
+        intents.extend(queue);
+    }
+
+    for intent in intents {
+        match intent {
+            Some(intent) => log.intents.push(intent),
+            None => warn!("Intent refers to something the game log cannot name"),
+        }
+    }
+}
+
+/// Writes the game log whenever intents were added to it, so a crash loses
+/// none of them. How the game stands is only written when the app exits,
+/// once every intent has taken effect.
+fn save_game_log(world: &mut World) {
+    let exiting = !world.resource::<Messages<AppExit>>().is_empty();
+    if !exiting && !world.is_resource_changed::<GameLog>() {
+        return;
+    }
+    let (Some(file), Some(log)) = (
+        world.get_resource::<GameLogFile>().cloned(),
+        world.get_resource::<GameLog>().cloned(),
+    ) else {
+        return;
+    };
+    let log = GameLog {
+        final_state: exiting.then(|| GameSnapshot::take(world)),
+        ..log
+    };
+
+    let saved = ron::ser::to_string_pretty(&log, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())
+        .and_then(|text| std::fs::write(&file.0, text).map_err(|e| e.to_string()));
+    match saved {
+        Ok(()) if exiting => info!("Saved the game log to {}", file.0.display()),
+        Ok(()) => {}
+        Err(e) => error!("Could not save the game log to {}: {}", file.0.display(), e),
+    }
+}
+
+pub struct GameLogPlugin;
+
+impl Plugin for GameLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<IntentSent>()
+            .add_systems(PostUpdate, record_intents.run_if(resource_exists::<GameLog>))
+            .add_systems(Last, save_game_log);
+    }
+}
+
+/// Starts a headless game set up the way `log` was and waits until the
+/// opening hands are dealt.
+pub fn start_replay(log: &GameLog) -> Result<App, GameError> {
+    let mut app = headless_app();
+    app.insert_resource(RulesetPath(log.ruleset.clone()))
+        .insert_resource(PlayerDecks(log.decks.clone()))
+        .insert_resource(GameSeed(log.seed));
+    wait_until_ready(app)
+}
+
+/// A game without a window whose every update is one fixed step, however
+/// long it really took.
+pub(crate) fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        StatesPlugin,
+        GamePlugin,
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / 64.0,
+    )));
+    app
+}
+
+/// Updates `app` until its assets are loaded and the game is set up.
+pub(crate) fn wait_until_ready(mut app: App) -> Result<App, GameError> {
+    for _ in 0..500 {
+        app.update();
+        if app.world().resource::<State<LoadState>>().get() == &LoadState::Ready {
+            settle(&mut app);
+            return Ok(app);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    Err(GameError::LoadError("assets did not finish loading".to_string()))
+}
+
+pub(crate) fn settle(app: &mut App) {
+    for _ in 0..SETTLE_FRAMES {
+        app.update();
+    }
+}
+
+/// Plays the logged game again and returns how it ends.
+pub fn replay(log: &GameLog) -> Result<GameSnapshot, GameError> {
+    let mut app = start_replay(log)?;
+    for intent in &log.intents {
+        send_intent(app.world_mut(), intent)?;
+        settle(&mut app);
+    }
+    Ok(GameSnapshot::take(app.world_mut()))
+}
+
+/// Plays the logged game again and checks it ends the way it did.
+pub fn verify(log: &GameLog) -> Result<(), GameError> {
+    let Some(expected) = &log.final_state else {
+        return Err(GameError::Incomplete("game log without a final state"));
+    };
+    let actual = replay(log)?;
+    if actual == *expected {
+        Ok(())
+    } else {
+        Err(GameError::ReplayDiverged {
+            expected: Box::new(expected.clone()),
+            actual: Box::new(actual),
+        })
+    }
+}
+
+fn send_intent(world: &mut World, intent: &Intent) -> Result<(), GameError> {
+    match intent {
+        Intent::PlayCard { card, position } => {
+            let card = resolve(world, *card)?;
+            let hand_position = world
+                .get::<Owner>(card)
+                .and_then(|owner| world.get::<Hand>(owner.0))
+                .and_then(|hand| hand.iter().position(|c| c == card))
+                .ok_or(GameError::CardNotFound)?;
+            world.write_intent(CardPlayRequested {
+                card,
+                hand_position,
+                position: *position,
+            });
+        }
+        Intent::Move { creature, from, to } => {
+            let entity = resolve(world, *creature)?;
+            world.write_intent(MoveRequest {
+                entity,
+                from: *from,
+                to: *to,
+            });
+        }
+        Intent::Attack { attacker, target } => {
+            let attacker = resolve(world, *attacker)?;
+            let target = resolve(world, *target)?;
+            world.write_intent(AttackRequest { attacker, target });
+        }
+        Intent::Choice(choice) => {
+            let choice = match choice {
+                LoggedChoice::Option(index) => ChoiceMade::Option(*index),
+                LoggedChoice::Entities(entities) => ChoiceMade::Entities(
+                    entities
+                        .iter()
+                        .map(|&entity| resolve(world, entity))
+                        .collect::<Result<_, _>>()?,
+                ),
+                LoggedChoice::Cancelled => ChoiceMade::Cancelled,
+            };
+            world.write_intent(choice);
+        }
+        Intent::PassPriority { seat } => {
+            let player = resolve(world, LoggedEntity::Player(*seat))?;
+            world.write_intent(PassPriority { player });
+        }
+        Intent::EndTurn => {
+            world.write_intent(EndTurnPressed);
+        }
+        Intent::Undo => {
+            world.write_intent(UndoPressed);
+        }
+    }
+    Ok(())
+}
+
+/// The entity the log refers to as `entity` in this run.
+fn resolve(world: &mut World, entity: LoggedEntity) -> Result<Entity, GameError> {
+    let found = match entity {
+        LoggedEntity::Card(id) => world
+            .query::<(Entity, &InPlayID)>()
+            .iter(world)
+            .find(|(_, card)| **card == id)
+            .map(|(card, _)| card),
+        LoggedEntity::Tile(position) => world
+            .query::<(Entity, &Position)>()
+            .iter(world)
+            .find(|(_, tile)| tile.0 == position)
+            .map(|(tile, _)| tile),
+        LoggedEntity::Player(seat) => world
+            .query::<(Entity, &Player)>()
+            .iter(world)
+            .find(|(_, player)| player.number == seat)
+            .map(|(player, _)| player),
+        LoggedEntity::Base(seat) => {
+            let player = resolve(world, LoggedEntity::Player(seat))?;
+            world
+                .query_filtered::<(Entity, &Owner), With<PlayerBase>>()
+                .iter(world)
+                .find(|(_, owner)| owner.0 == player)
+                .map(|(base, _)| base)
+        }
+    };
+    found.ok_or_else(|| GameError::NotFound(format!("{:?}", entity)))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{card::InHand, player::TurnPlayer};
+
+    /// Starts a game from `seed` in which both players kept their hands and
+    /// the first one played a creature next to their base. Returns the game
+    /// and the creature.
+    pub(crate) fn game_with_creature_in_play(seed: u64) -> (App, Entity) {
+        let log = GameLog::new(seed, RulesetPath::default().0, PlayerDecks::default().0);
+        let mut app = start_replay(&log).unwrap();
+        for _ in 0..2 {
+            app.world_mut().write_intent(ChoiceMade::Entities(vec![]));
+            settle(&mut app);
+        }
+        let world = app.world_mut();
+        let player = world
+            .query_filtered::<Entity, With<TurnPlayer>>()
+            .single(world)
+            .unwrap();
+        let hand = world.get::<Hand>(player).unwrap().clone();
+        let (hand_position, card) = hand
+            .iter()
+            .enumerate()
+            .find(|(_, card)| world.get::<CreatureCard>(*card).is_some())
+            .unwrap();
+        assert!(world.get::<InHand>(card).is_some());
+        world.write_intent(CardPlayRequested {
+            card,
+            hand_position,
+            position: U16Vec2::new(3, 6),
+        });
+        settle(&mut app);
+        (app, card)
+    }
+
+    #[test]
+    fn a_recorded_game_replays_to_the_same_state() {
+        // Both players keep their hands, then the first one plays a creature
+        // next to their base and ends the turn.
+        let (mut app, _) = game_with_creature_in_play(5);
+        app.world_mut().write_intent(EndTurnPressed);
+        settle(&mut app);
+
+        let mut log = app.world().resource::<GameLog>().clone();
+        log.final_state = Some(GameSnapshot::take(app.world_mut()));
+        assert_eq!(log.intents.len(), 4);
+        assert_eq!(log.final_state.as_ref().unwrap().creatures.len(), 1);
+        assert_eq!(log.final_state.as_ref().unwrap().turn, 2);
+
+        let text = ron::ser::to_string(&log).unwrap();
+        assert_eq!(ron::de::from_str::<GameLog>(&text).unwrap(), log);
+        verify(&log).unwrap();
+    }
+
+    #[test]
+    fn intents_are_written_to_the_log_file_in_the_order_they_were_sent() {
+        let log = GameLog::new(5, RulesetPath::default().0, PlayerDecks::default().0);
+        let mut app = start_replay(&log).unwrap();
+        let path = std::env::temp_dir().join(format!("game-{}.log.ron", std::process::id()));
+        app.insert_resource(GameLogFile(path.clone()));
+
+        let world = app.world_mut();
+        world.write_intent(UndoPressed);
+        world.write_intent(ChoiceMade::Entities(vec![]));
+        app.update();
+
+        let expected = vec![Intent::Undo, Intent::Choice(LoggedChoice::Entities(vec![]))];
+        assert_eq!(app.world().resource::<GameLog>().intents, expected);
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let written = ron::de::from_str::<GameLog>(&text).unwrap();
+        assert_eq!(written.intents, expected);
+        assert_eq!(written.final_state, None);
+    }
+}
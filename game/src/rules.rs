@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use bevy::{asset::Asset, ecs::resource::Resource, math::U16Vec2, reflect::TypePath};
+use serde::{Deserialize, Serialize};
+
+use crate::board::effect::EffectType;
+
+/// Gold a player collects at the start of their turn. Starts at `base` and
+/// grows by `per_round` every round, up to `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldIncome {
+    pub base: u16,
+    #[serde(default)]
+    pub per_round: u16,
+    pub max: u16,
+}
+
+impl GoldIncome {
+    /// The income in round number `round`, counting from 1.
+    pub fn for_round(&self, round: u32) -> u16 {
+        let growth = u16::try_from(round.saturating_sub(1)).unwrap_or(u16::MAX);
+        self.base
+            .saturating_add(self.per_round.saturating_mul(growth))
+            .min(self.max)
+    }
+}
+
+/// What happens when a player has to draw from an empty deck.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeckOut {
+    /// The player loses the game.
+    Lose,
+    /// The player takes fatigue damage, one more every time.
+    #[default]
+    Fatigue,
+}
+
+/// Limits on the decks players may bring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckRules {
+    pub min_size: u16,
+    pub max_size: u16,
+    /// Copies of any one card a deck may hold.
+    pub max_copies: u16,
+}
+
+/// The rules of the game that differ between formats, read from a
+/// `*.ruleset.ron` file in `assets/rules`. Rules a file leaves out keep their
+/// standard values.
+#[derive(Asset, TypePath, Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ruleset {
+    pub board_width: u16,
+    pub board_height: u16,
+    /// Health of each player's base.
+    pub base_health: u16,
+    pub player_health: u16,
+    pub starting_gold: u16,
+    /// Cards each player draws before the first turn.
+    pub starting_hand: u16,
+    /// Cards the turn player draws at the start of their turn.
+    pub cards_per_turn: u16,
+    pub income: GoldIncome,
+    /// Cards a player may keep in hand at the end of their turn; the rest
+    /// are discarded.
+    pub max_hand_size: u16,
+    pub deck_out: DeckOut,
+    pub deck: DeckRules,
+    /// Extra movement points needed to leave a tile with the named effect,
+    /// replacing the cost its file in `assets/effects` gives.
+    pub movement_costs: BTreeMap<EffectType, u16>,
+    /// Seeds the game's randomness; without one every game is different.
+    pub seed: Option<u64>,
+}
+
+impl Ruleset {
+    pub fn board_size(&self) -> U16Vec2 {
+        U16Vec2::new(self.board_width, self.board_height)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self {
+            board_width: 24,
+            board_height: 12,
+            base_health: 10,
+            player_health: 10,
+            starting_gold: 10,
+            starting_hand: 5,
+            cards_per_turn: 1,
+            income: GoldIncome {
+                base: 2,
+                per_round: 1,
+                max: 10,
+            },
+            max_hand_size: 8,
+            deck_out: DeckOut::Fatigue,
+            deck: DeckRules {
+                min_size: 30,
+                max_size: 60,
+                max_copies: 4,
+            },
+            movement_costs: BTreeMap::new(),
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn income_grows_every_round_up_to_its_maximum() {
+        let income = GoldIncome {
+            base: 2,
+            per_round: 3,
+            max: 10,
+        };
+        assert_eq!(income.for_round(1), 2);
+        assert_eq!(income.for_round(3), 8);
+        assert_eq!(income.for_round(4), 10);
+        assert_eq!(income.for_round(u32::MAX), 10);
+    }
+
+    #[test]
+    fn rulesets_replace_the_movement_cost_of_named_effects() {
+        let rules: Ruleset = ron::de::from_str("Ruleset(movement_costs: { Slow: 2 })").unwrap();
+        assert_eq!(rules.movement_costs.get(&EffectType::new("Slow")), Some(&2));
+        assert_eq!(
+            ron::de::from_str::<Ruleset>(&ron::to_string(&rules).unwrap()).unwrap(),
+            rules
+        );
+    }
+}
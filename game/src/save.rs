@@ -0,0 +1,1060 @@
+//! Saving a game in progress to a file and restoring it into a fresh `App`.
+//! Entities differ between runs, so a save refers to game objects the way the
+//! game log does and tile effects by their place in the save. Selections in
+//! progress are not saved; a game saved while a card or figure is selected
+//! continues without the selection.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use bevy::{math::U16Vec2, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameRng, GameSeed, GameSetup,
+    actions::{
+        AbilityData, Action, Actions,
+        execute::{AbilityContext, AbilityCursor, Activation, AwaitingChoice, AwaitingChoiceKind},
+        hooks::HookUsedThisTurn,
+        stack::{EffectStack, Priority},
+        timing::ActionTiming,
+    },
+    board::{
+        BoardRes, PlayerBase,
+        combat::AttackedThisTurn,
+        effect::{Effect, EffectDuration, EffectRegistry, EffectType},
+        tile::{Occupant, Position, SetTrap, Tile},
+    },
+    card::{
+        CardBundle, CreatureCard, CurrentAttack, CurrentDefense, CurrentMovementPoints, FaceDown,
+        FromRegistry, InDeck, InGraveyard, InHand, OnBoard, SetOnTile,
+        card_registry::CardRegistry,
+        in_play_id::InPlayID,
+        modifiers::{Stat, StatDuration, StatModifierEntry, StatModifiers},
+    },
+    components::{Health, Owner},
+    def::{effect::EffectDef, loader::LoadState, trigger::AbilityDef},
+    error::GameError,
+    mulligan::Mulligan,
+    player::{Deck, Graveyard, Hand, Player, PlayerResources, TurnPlayer},
+    replay::{headless_app, wait_until_ready},
+    rules::Ruleset,
+    turn_controller::{TurnCounter, TurnEnding, TurnPhase, TurnState},
+};
+
+/// A game object as a save refers to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SavedEntity {
+    Card(InPlayID),
+    Tile(U16Vec2),
+    Player(u8),
+    Base(u8),
+    /// A tile effect, by its index in [`SavedGame::effects`].
+    Effect(usize),
+}
+
+/// An ability of a card or tile effect, by its place among the abilities of
+/// its holder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbilityRef {
+    pub holder: SavedEntity,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SavedContext {
+    pub current_target: Option<SavedEntity>,
+    pub chosen_entities: Vec<SavedEntity>,
+    pub pending_targets: Option<Vec<SavedEntity>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedAbility {
+    pub ability: AbilityDef,
+    /// The delays of activations waiting to fire and what they were started
+    /// with.
+    #[serde(default)]
+    pub queued: Vec<(ActionTiming, SavedContext)>,
+    /// Whether the once-per-turn hook of the ability already fired.
+    #[serde(default)]
+    pub used_this_turn: bool,
+}
+
+/// An ability on the effect stack with the effects it has left.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedCursor {
+    pub ability: AbilityRef,
+    pub effects: Vec<EffectDef>,
+    pub context: SavedContext,
+    /// The choice the ability waits on, if any.
+    #[serde(default)]
+    pub awaiting: Option<AwaitingChoiceKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardLocation {
+    Deck,
+    Hand,
+    Graveyard,
+    Board(U16Vec2),
+    /// Set as a trap on the tile.
+    Trap(U16Vec2),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedModifier {
+    pub source: Option<SavedEntity>,
+    pub stat: Stat,
+    pub delta: i16,
+    pub duration: StatDuration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedStats {
+    pub attack: u16,
+    pub health: u16,
+    pub max_health: u16,
+    pub movement_points: u16,
+    pub modifiers: Vec<SavedModifier>,
+    pub attacked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedCard {
+    pub id: InPlayID,
+    pub name: String,
+    pub owner: u8,
+    pub location: CardLocation,
+    #[serde(default)]
+    pub face_down: bool,
+    /// The current stats of creatures.
+    #[serde(default)]
+    pub stats: Option<SavedStats>,
+    pub abilities: Vec<SavedAbility>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedEffect {
+    pub effect: EffectType,
+    pub tile: U16Vec2,
+    pub owner: u8,
+    pub duration: u16,
+    pub abilities: Vec<SavedAbility>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub seat: u8,
+    pub health: u16,
+    pub max_health: u16,
+    pub gold: u16,
+    pub fatigue: u16,
+    pub base_health: Option<u16>,
+    /// Whether the player still decides on their mulligan.
+    #[serde(default)]
+    pub mulligan: bool,
+}
+
+/// Everything a game in progress is made of.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub seed: u64,
+    pub rng: GameRng,
+    pub ruleset: Ruleset,
+    pub turn: u32,
+    pub phase: TurnPhase,
+    pub state: Option<TurnState>,
+    #[serde(default)]
+    pub turn_ending: bool,
+    pub turn_player: u8,
+    pub players: Vec<SavedPlayer>,
+    /// Every card in play. Decks, hands and graveyards keep their order.
+    pub cards: Vec<SavedCard>,
+    pub effects: Vec<SavedEffect>,
+    /// The abilities on the effect stack, bottom first.
+    pub stack: Vec<SavedCursor>,
+    /// The seat holding priority and how many passed in a row.
+    pub priority: Option<u8>,
+    #[serde(default)]
+    pub passes: usize,
+}
+
+impl SavedGame {
+    /// The tile effects lying on the board or named by any saved ability.
+    pub fn effect_types(&self) -> Vec<&EffectType> {
+        let abilities = self
+            .cards
+            .iter()
+            .flat_map(|card| &card.abilities)
+            .chain(self.effects.iter().flat_map(|effect| &effect.abilities))
+            .flat_map(|saved| saved.ability.effect_types());
+        let stack = self
+            .stack
+            .iter()
+            .flat_map(|cursor| &cursor.effects)
+            .flat_map(EffectDef::effect_types);
+        self.effects
+            .iter()
+            .map(|effect| &effect.effect)
+            .chain(abilities)
+            .chain(stack)
+            .collect()
+    }
+
+    /// Saves how the game in `world` stands. Fails while the turn changes
+    /// hands, as the phase the game is in is about to change.
+    pub fn take(world: &mut World) -> Result<Self, GameError> {
+        if matches!(
+            world.resource::<NextState<TurnPhase>>(),
+            NextState::Pending(_) | NextState::PendingIfNeq(_)
+        ) {
+            return Err(GameError::WrongState);
+        }
+        let phase = world.resource::<State<TurnPhase>>().get().clone();
+        if matches!(phase, TurnPhase::Start | TurnPhase::End) {
+            return Err(GameError::WrongState);
+        }
+        let state = match world.get_resource::<NextState<TurnState>>() {
+            Some(NextState::Pending(state) | NextState::PendingIfNeq(state)) => Some(state.clone()),
+            _ => world
+                .get_resource::<State<TurnState>>()
+                .map(|state| state.get().clone()),
+        };
+        let state = state.map(|state| match state {
+            TurnState::CardSelected | TurnState::FigureSelected => TurnState::Idle,
+            state => state,
+        });
+
+        let turn_player = world
+            .query_filtered::<Entity, With<TurnPlayer>>()
+            .single(world)
+            .map_err(|_| GameError::PlayerNotFound)?;
+        let names = SavedNames::new(world);
+        let world = &*world;
+        let seat = |player: Entity| -> Result<u8, GameError> {
+            world
+                .get::<Player>(player)
+                .map(|player| player.number)
+                .ok_or(GameError::PlayerNotFound)
+        };
+
+        let mut players = Vec::new();
+        let mut cards = Vec::new();
+        for &(player, number) in &names.players {
+            let resources = world
+                .get::<PlayerResources>(player)
+                .ok_or(GameError::PlayerNotFound)?;
+            players.push(SavedPlayer {
+                seat: number,
+                health: resources.health,
+                max_health: resources.max_health,
+                gold: resources.gold,
+                fatigue: resources.fatigue,
+                base_health: names
+                    .bases
+                    .iter()
+                    .find(|(_, owner)| *owner == number)
+                    .and_then(|(base, _)| world.get::<Health>(*base))
+                    .map(Health::value),
+                mulligan: world.get::<Mulligan>(player).is_some(),
+            });
+
+            let deck = world
+                .get::<Deck>(player)
+                .into_iter()
+                .flat_map(|deck| deck.iter());
+            let hand = world
+                .get::<Hand>(player)
+                .into_iter()
+                .flat_map(|hand| hand.iter());
+            let graveyard = world
+                .get::<Graveyard>(player)
+                .into_iter()
+                .flat_map(|graveyard| graveyard.iter());
+            let zones = deck
+                .map(|card| (card, CardLocation::Deck))
+                .chain(hand.map(|card| (card, CardLocation::Hand)))
+                .chain(graveyard.map(|card| (card, CardLocation::Graveyard)));
+            for (card, location) in zones {
+                cards.push(names.card(world, card, location)?);
+            }
+        }
+        for &(tile, position) in &names.tiles {
+            if let Some(card) = world.get::<Occupant>(tile)
+                && world.get::<InPlayID>(card.get()).is_some()
+            {
+                cards.push(names.card(world, card.get(), CardLocation::Board(position))?);
+            }
+            if let Some(trap) = world.get::<SetTrap>(tile) {
+                cards.push(names.card(world, trap.get(), CardLocation::Trap(position))?);
+            }
+        }
+
+        let mut effects = Vec::new();
+        for &(effect, position) in &names.effects {
+            let (Some(effect_type), Some(duration), Some(owner)) = (
+                world.get::<EffectType>(effect),
+                world.get::<EffectDuration>(effect),
+                world.get::<Owner>(effect),
+            ) else {
+                continue;
+            };
+            effects.push(SavedEffect {
+                effect: effect_type.clone(),
+                tile: position,
+                owner: seat(owner.0)?,
+                duration: duration.0,
+                abilities: names.abilities(world, effect),
+            });
+        }
+
+        let mut stack = Vec::new();
+        for activation in world.resource::<EffectStack>().iter() {
+            let (Some(cursor), Some(ability_ref)) = (
+                world.get::<AbilityCursor>(activation),
+                world
+                    .get::<Activation>(activation)
+                    .and_then(|activation| names.ability(world, activation.ability)),
+            ) else {
+                warn!("Ability {} on the stack cannot be saved", activation);
+                continue;
+            };
+            stack.push(SavedCursor {
+                ability: ability_ref,
+                effects: cursor.stack.clone(),
+                context: names.context(&cursor.context),
+                awaiting: world
+                    .get::<AwaitingChoice>(activation)
+                    .map(|awaiting| awaiting.kind.clone()),
+            });
+        }
+
+        let priority = world.resource::<Priority>();
+
+        Ok(Self {
+            seed: world
+                .get_resource::<GameSeed>()
+                .ok_or(GameError::Incomplete("game not set up"))?
+                .0,
+            rng: world.resource::<GameRng>().clone(),
+            ruleset: world.resource::<Ruleset>().clone(),
+            turn: world.resource::<TurnCounter>().0,
+            phase,
+            state,
+            turn_ending: world.contains_resource::<TurnEnding>(),
+            turn_player: seat(turn_player)?,
+            players,
+            cards,
+            effects,
+            stack,
+            priority: priority.holder().map(seat).transpose()?,
+            passes: priority.passes(),
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self, GameError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| GameError::LoadError(e.to_string()))?;
+        ron::de::from_str(&text).map_err(|e| GameError::LoadError(e.to_string()))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), GameError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| GameError::SaveError(e.to_string()))?;
+        std::fs::write(path, text).map_err(|e| GameError::SaveError(e.to_string()))
+    }
+
+    /// Continues the game saved at `path` in `app` once the assets are
+    /// loaded. Saves name tile effects, so only what the game is set up with
+    /// before then is read right away.
+    pub fn restore_file_into(path: &Path, app: &mut App) -> Result<(), GameError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| GameError::LoadError(e.to_string()))?;
+        let header: SaveHeader =
+            ron::de::from_str(&text).map_err(|e| GameError::LoadError(e.to_string()))?;
+        app.insert_state(header.phase)
+            .insert_resource(GameSeed(header.seed))
+            .insert_resource(SaveText(text));
+        Ok(())
+    }
+
+    /// Continues this game in a headless app, like a replay.
+    pub fn restore(self) -> Result<App, GameError> {
+        let mut app = headless_app();
+        self.restore_into(&mut app);
+        wait_until_ready(app)
+    }
+
+    /// Continues this game in `app` instead of dealing a new one once the
+    /// assets are loaded. `app` needs the `GamePlugin` added already.
+    pub fn restore_into(self, app: &mut App) {
+        app.insert_state(self.phase.clone())
+            .insert_resource(GameSeed(self.seed))
+            .insert_resource(self);
+    }
+}
+
+/// The parts of a save read before the assets are loaded.
+#[derive(Deserialize)]
+#[serde(rename = "SavedGame")]
+struct SaveHeader {
+    seed: u64,
+    phase: TurnPhase,
+}
+
+/// A save file waiting for the tile effects it names to be registered.
+#[derive(Resource, Debug, Clone)]
+struct SaveText(String);
+
+/// How the save names the entities of a game, and the tiles and effects in
+/// the order they are saved.
+struct SavedNames {
+    names: HashMap<Entity, SavedEntity>,
+    players: Vec<(Entity, u8)>,
+    bases: Vec<(Entity, u8)>,
+    tiles: Vec<(Entity, U16Vec2)>,
+    effects: Vec<(Entity, U16Vec2)>,
+}
+
+impl SavedNames {
+    fn new(world: &mut World) -> Self {
+        let mut players: Vec<(Entity, u8)> = world
+            .query::<(Entity, &Player)>()
+            .iter(world)
+            .map(|(entity, player)| (entity, player.number))
+            .collect();
+        players.sort_by_key(|(_, number)| *number);
+        let bases: Vec<(Entity, u8)> = world
+            .query_filtered::<(Entity, &Owner), With<PlayerBase>>()
+            .iter(world)
+            .filter_map(|(base, owner)| {
+                let (_, number) = players.iter().find(|(player, _)| *player == owner.0)?;
+                Some((base, *number))
+            })
+            .collect();
+        let cards: Vec<(Entity, InPlayID)> = world
+            .query::<(Entity, &InPlayID)>()
+            .iter(world)
+            .map(|(card, id)| (card, *id))
+            .collect();
+        let mut tiles: Vec<(Entity, U16Vec2)> = world
+            .query_filtered::<(Entity, &Position), With<Tile>>()
+            .iter(world)
+            .map(|(tile, position)| (tile, position.0))
+            .collect();
+        tiles.sort_by_key(|(_, position)| (position.x, position.y));
+        // Effects are saved tile by tile in the order they were laid.
+        let effects: Vec<(Entity, U16Vec2)> = tiles
+            .iter()
+            .flat_map(|&(tile, position)| {
+                world
+                    .get::<Children>(tile)
+                    .into_iter()
+                    .flat_map(|children| children.iter())
+                    .filter(|&child| world.get::<EffectType>(child).is_some())
+                    .map(move |effect| (effect, position))
+            })
+            .collect();
+
+        let names = players
+            .iter()
+            .map(|&(player, number)| (player, SavedEntity::Player(number)))
+            .chain(
+                bases
+                    .iter()
+                    .map(|&(base, number)| (base, SavedEntity::Base(number))),
+            )
+            .chain(
+                cards
+                    .iter()
+                    .map(|&(card, id)| (card, SavedEntity::Card(id))),
+            )
+            .chain(
+                tiles
+                    .iter()
+                    .map(|&(tile, position)| (tile, SavedEntity::Tile(position))),
+            )
+            .chain(
+                effects
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &(effect, _))| (effect, SavedEntity::Effect(index))),
+            )
+            .collect();
+        Self {
+            names,
+            players,
+            bases,
+            tiles,
+            effects,
+        }
+    }
+
+    fn get(&self, entity: Entity) -> Option<SavedEntity> {
+        self.names.get(&entity).copied()
+    }
+
+    /// Saves `context`. Entities the save cannot name, like expired effects,
+    /// are left out.
+    fn context(&self, context: &AbilityContext) -> SavedContext {
+        SavedContext {
+            current_target: context.current_target.and_then(|entity| self.get(entity)),
+            chosen_entities: context
+                .chosen_entities
+                .iter()
+                .filter_map(|&entity| self.get(entity))
+                .collect(),
+            pending_targets: context.pending_targets.as_ref().map(|targets| {
+                targets
+                    .iter()
+                    .filter_map(|&entity| self.get(entity))
+                    .collect()
+            }),
+        }
+    }
+
+    fn ability(&self, world: &World, ability: Entity) -> Option<AbilityRef> {
+        let caster = world.get::<Action>(ability)?.caster;
+        let index = world
+            .get::<Actions>(caster)?
+            .iter()
+            .position(|a| a == ability)?;
+        Some(AbilityRef {
+            holder: self.get(caster)?,
+            index,
+        })
+    }
+
+    fn abilities(&self, world: &World, holder: Entity) -> Vec<SavedAbility> {
+        let Some(actions) = world.get::<Actions>(holder) else {
+            return Vec::new();
+        };
+        actions
+            .iter()
+            .filter_map(|ability| {
+                let data = world.get::<AbilityData>(ability)?;
+                let queued = world
+                    .get::<Children>(ability)
+                    .into_iter()
+                    .flat_map(|activations| activations.iter())
+                    .filter_map(|activation| {
+                        let timing = world.get::<ActionTiming>(activation)?;
+                        let context = world.get::<AbilityContext>(activation)?;
+                        Some((*timing, self.context(context)))
+                    })
+                    .collect();
+                Some(SavedAbility {
+                    ability: data.0.clone(),
+                    queued,
+                    used_this_turn: world.get::<HookUsedThisTurn>(ability).is_some(),
+                })
+            })
+            .collect()
+    }
+
+    fn card(
+        &self,
+        world: &World,
+        card: Entity,
+        location: CardLocation,
+    ) -> Result<SavedCard, GameError> {
+        let (Some(&id), Some(name), Some(owner)) = (
+            world.get::<InPlayID>(card),
+            world.get::<Name>(card),
+            world.get::<Owner>(card),
+        ) else {
+            return Err(GameError::CardNotFound);
+        };
+        let stats = world.get::<CreatureCard>(card).and_then(|_| {
+            Some(SavedStats {
+                attack: world.get::<CurrentAttack>(card)?.0,
+                health: world.get::<CurrentDefense>(card)?.0,
+                max_health: world.get::<Health>(card)?.value(),
+                movement_points: world.get::<CurrentMovementPoints>(card)?.0,
+                modifiers: world
+                    .get::<StatModifiers>(card)?
+                    .iter()
+                    .map(|entry| SavedModifier {
+                        source: entry.source.and_then(|source| self.get(source)),
+                        stat: entry.stat,
+                        delta: entry.delta,
+                        duration: entry.duration,
+                    })
+                    .collect(),
+                attacked: world.get::<AttackedThisTurn>(card).is_some(),
+            })
+        });
+        Ok(SavedCard {
+            id,
+            name: name.as_str().to_string(),
+            owner: world
+                .get::<Player>(owner.0)
+                .ok_or(GameError::PlayerNotFound)?
+                .number,
+            location,
+            face_down: world.get::<FaceDown>(card).is_some(),
+            stats,
+            abilities: self.abilities(world, card),
+        })
+    }
+}
+
+/// Reads the waiting save file in full. A save naming unknown tile effects
+/// or cards ends the game.
+fn read_saved_game(
+    text: Res<SaveText>,
+    effects: Res<EffectRegistry>,
+    mut commands: Commands,
+    mut exit: MessageWriter<AppExit>,
+) {
+    commands.remove_resource::<SaveText>();
+    let saved = ron::de::from_str::<SavedGame>(&text.0)
+        .map_err(|e| e.to_string())
+        .and_then(|saved| {
+            effects
+                .check(saved.effect_types())
+                .map_err(|e| e.to_string())?;
+            Ok(saved)
+        });
+    match saved {
+        Ok(saved) => commands.insert_resource(saved),
+        Err(e) => {
+            error!("Could not load the saved game: {e}");
+            exit.write(AppExit::error());
+        }
+    }
+}
+
+/// The saved game's rules take the place of the loaded ruleset.
+fn apply_saved_ruleset(saved: Res<SavedGame>, mut rules: ResMut<Ruleset>) {
+    rules.clone_from(&saved.ruleset);
+}
+
+/// Sets the saved game up in place of dealing a new one.
+fn restore_game(world: &mut World) -> Result {
+    let Some(saved) = world.remove_resource::<SavedGame>() else {
+        return Ok(());
+    };
+
+    let mut entities: HashMap<SavedEntity, Entity> = HashMap::new();
+    let players: Vec<(Entity, u8)> = world
+        .query::<(Entity, &Player)>()
+        .iter(world)
+        .map(|(entity, player)| (entity, player.number))
+        .collect();
+    for &(player, number) in &players {
+        entities.insert(SavedEntity::Player(number), player);
+    }
+    for (base, owner) in world
+        .query_filtered::<(Entity, &Owner), With<PlayerBase>>()
+        .iter(world)
+    {
+        if let Some(&(_, number)) = players.iter().find(|(player, _)| *player == owner.0) {
+            entities.insert(SavedEntity::Base(number), base);
+        }
+    }
+    for (&position, &tile) in world.resource::<BoardRes>().iter() {
+        entities.insert(SavedEntity::Tile(position), tile);
+    }
+
+    for player in &saved.players {
+        let entity = find(&entities, SavedEntity::Player(player.seat))?;
+        let mut entity = world.entity_mut(entity);
+        entity.insert(PlayerResources {
+            health: player.health,
+            max_health: player.max_health,
+            gold: player.gold,
+            fatigue: player.fatigue,
+        });
+        if player.mulligan {
+            entity.insert(Mulligan);
+        }
+        if player.seat == saved.turn_player {
+            entity.insert(TurnPlayer);
+        } else {
+            entity.remove::<TurnPlayer>();
+        }
+        if let Some(health) = player.base_health
+            && let Some(&base) = entities.get(&SavedEntity::Base(player.seat))
+        {
+            world.entity_mut(base).insert(Health(health));
+        }
+    }
+
+    let bundles = {
+        let registry = world.resource::<CardRegistry>();
+        saved
+            .cards
+            .iter()
+            .map(|card| {
+                registry
+                    .id_of_name(&card.name)
+                    .and_then(|id| CardBundle::from_registry(registry, id))
+                    .ok_or_else(|| GameError::NotFound(format!("card {}", card.name)))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    // Spawn every card and effect before anything refers to them.
+    let mut abilities: HashMap<SavedEntity, Vec<Entity>> = HashMap::new();
+    let mut commands = world.commands();
+    for (card, bundle) in saved.cards.iter().zip(bundles) {
+        let owner = find(&entities, SavedEntity::Player(card.owner))?;
+        let entity = bundle.spawn(&mut commands, (Owner(owner), card.id));
+        // Spawning the cards in saved order keeps decks, hands and
+        // graveyards in order.
+        match card.location {
+            CardLocation::Deck => commands.entity(entity).insert(InDeck { parent: owner }),
+            CardLocation::Hand => commands.entity(entity).insert(InHand { parent: owner }),
+            CardLocation::Graveyard => commands.entity(entity).insert(InGraveyard { owner }),
+            CardLocation::Board(position) => commands.entity(entity).insert(OnBoard {
+                position: find(&entities, SavedEntity::Tile(position))?,
+            }),
+            CardLocation::Trap(position) => commands.entity(entity).insert(SetOnTile {
+                tile: find(&entities, SavedEntity::Tile(position))?,
+            }),
+        };
+        if card.face_down {
+            commands.entity(entity).insert(FaceDown);
+        }
+        entities.insert(SavedEntity::Card(card.id), entity);
+        abilities.insert(
+            SavedEntity::Card(card.id),
+            spawn_abilities(&mut commands, entity, &card.abilities),
+        );
+    }
+    for (index, effect) in saved.effects.iter().enumerate() {
+        let tile = find(&entities, SavedEntity::Tile(effect.tile))?;
+        let owner = find(&entities, SavedEntity::Player(effect.owner))?;
+        let entity = commands
+            .spawn((
+                ChildOf(tile),
+                Effect::new(effect.effect.clone(), effect.duration, owner),
+            ))
+            .id();
+        entities.insert(SavedEntity::Effect(index), entity);
+        abilities.insert(
+            SavedEntity::Effect(index),
+            spawn_abilities(&mut commands, entity, &effect.abilities),
+        );
+    }
+    world.flush();
+
+    for card in &saved.cards {
+        let Some(stats) = &card.stats else {
+            continue;
+        };
+        let modifiers = stats
+            .modifiers
+            .iter()
+            .map(|modifier| {
+                Ok(StatModifierEntry {
+                    source: modifier
+                        .source
+                        .map(|source| find(&entities, source))
+                        .transpose()?,
+                    stat: modifier.stat,
+                    delta: modifier.delta,
+                    duration: modifier.duration,
+                })
+            })
+            .collect::<Result<Vec<_>, GameError>>()?;
+        let mut entity = world.entity_mut(find(&entities, SavedEntity::Card(card.id))?);
+        entity.insert((
+            CurrentAttack(stats.attack),
+            CurrentDefense(stats.health),
+            Health(stats.max_health),
+            CurrentMovementPoints(stats.movement_points),
+            StatModifiers::new(modifiers),
+        ));
+        if stats.attacked {
+            entity.insert(AttackedThisTurn);
+        }
+    }
+
+    let holders = saved
+        .cards
+        .iter()
+        .map(|card| (SavedEntity::Card(card.id), &card.abilities))
+        .chain(
+            saved
+                .effects
+                .iter()
+                .enumerate()
+                .map(|(index, effect)| (SavedEntity::Effect(index), &effect.abilities)),
+        );
+    for (holder, saved_abilities) in holders {
+        let caster = find(&entities, holder)?;
+        for (&ability, saved_ability) in abilities[&holder].iter().zip(saved_abilities) {
+            for (timing, context) in &saved_ability.queued {
+                let context = restore_context(context, &entities)?;
+                world.spawn((
+                    Activation { ability, caster },
+                    ChildOf(ability),
+                    *timing,
+                    context,
+                ));
+            }
+            if saved_ability.used_this_turn {
+                world.entity_mut(ability).insert(HookUsedThisTurn);
+            }
+        }
+    }
+
+    // Starting the cursors bottom first pushes them onto the effect stack in
+    // the order they were on.
+    for cursor in &saved.stack {
+        let ability = abilities
+            .get(&cursor.ability.holder)
+            .and_then(|abilities| abilities.get(cursor.ability.index))
+            .copied()
+            .ok_or_else(|| GameError::NotFound(format!("{:?}", cursor.ability)))?;
+        let context = restore_context(&cursor.context, &entities)?;
+        let caster = find(&entities, cursor.ability.holder)?;
+        let mut entity = world.spawn((Activation { ability, caster }, ChildOf(ability)));
+        let activation = entity.id();
+        entity.insert(AbilityCursor {
+            stack: cursor.effects.clone(),
+            context,
+        });
+        if let Some(kind) = &cursor.awaiting {
+            entity.insert(AwaitingChoice {
+                cursor: activation,
+                kind: kind.clone(),
+            });
+        }
+    }
+    let holder = saved
+        .priority
+        .map(|seat| find(&entities, SavedEntity::Player(seat)))
+        .transpose()?;
+    world.insert_resource(Priority::restore(holder, saved.passes));
+
+    world.insert_resource(TurnCounter(saved.turn));
+    if saved.turn_ending {
+        world.init_resource::<TurnEnding>();
+    }
+    if let Some(state) = saved.state {
+        world.resource_mut::<NextState<TurnState>>().set(state);
+    }
+    world.insert_resource(saved.rng);
+    info!("Restored the saved game at turn {}", saved.turn);
+    Ok(())
+}
+
+fn spawn_abilities(
+    commands: &mut Commands,
+    holder: Entity,
+    abilities: &[SavedAbility],
+) -> Vec<Entity> {
+    abilities
+        .iter()
+        .map(|ability| {
+            commands
+                .spawn((
+                    AbilityData(ability.ability.clone()),
+                    Action { caster: holder },
+                    ChildOf(holder),
+                ))
+                .id()
+        })
+        .collect()
+}
+
+fn restore_context(
+    context: &SavedContext,
+    entities: &HashMap<SavedEntity, Entity>,
+) -> Result<AbilityContext, GameError> {
+    let find_all = |saved: &[SavedEntity]| {
+        saved
+            .iter()
+            .map(|&entity| find(entities, entity))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    Ok(AbilityContext {
+        current_target: context
+            .current_target
+            .map(|entity| find(entities, entity))
+            .transpose()?,
+        chosen_entities: find_all(&context.chosen_entities)?,
+        pending_targets: context
+            .pending_targets
+            .as_deref()
+            .map(find_all)
+            .transpose()?,
+    })
+}
+
+fn find(entities: &HashMap<SavedEntity, Entity>, entity: SavedEntity) -> Result<Entity, GameError> {
+    entities
+        .get(&entity)
+        .copied()
+        .ok_or_else(|| GameError::NotFound(format!("{:?}", entity)))
+}
+
+/// Where F5 saves the game.
+#[derive(Resource, Debug, Clone)]
+pub struct SaveFile(pub PathBuf);
+
+/// Saves the game when F5 is pressed.
+fn save_on_key(world: &mut World) {
+    if !world
+        .resource::<ButtonInput<KeyCode>>()
+        .just_pressed(KeyCode::F5)
+    {
+        return;
+    }
+    let file = world.resource::<SaveFile>().clone();
+    match SavedGame::take(world).and_then(|saved| saved.write(&file.0)) {
+        Ok(()) => info!("Saved the game to {}", file.0.display()),
+        Err(e) => error!("Could not save the game to {}: {}", file.0.display(), e),
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(LoadState::Ready),
+            (
+                read_saved_game.run_if(resource_exists::<SaveText>),
+                (
+                    apply_saved_ruleset.before(BoardRes::setup_board),
+                    restore_game
+                        .after(BoardRes::setup_player_bases)
+                        .in_set(GameSetup),
+                )
+                    .run_if(resource_exists::<SavedGame>),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Last,
+            save_on_key
+                .run_if(resource_exists::<SaveFile>)
+                .run_if(in_state(LoadState::Ready)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{ModifyStats, value_source::StatModifier},
+        board::EffectRequested,
+        replay::{GameSnapshot, settle, tests::game_with_creature_in_play},
+        turn_controller::{ChoiceMade, EndTurnPressed},
+    };
+
+    #[test]
+    fn a_restored_game_continues_like_the_saved_one() {
+        // The first player plays a creature and lays a burning effect under
+        // it, which weakens it and waits on a choice for its next target.
+        let (mut app, creature) = game_with_creature_in_play(11);
+        let world = app.world_mut();
+        let player = world.get::<Owner>(creature).unwrap().0;
+        let burning = EffectType::new("Burning");
+        world.write_message(EffectRequested {
+            effect: Effect::new(burning.clone(), 3, player),
+            indices: vec![U16Vec2::new(3, 6)],
+        });
+        settle(&mut app);
+        let world = app.world_mut();
+        let effect = world
+            .query::<(Entity, &EffectType)>()
+            .iter(world)
+            .find(|(_, effect)| **effect == burning)
+            .unwrap()
+            .0;
+        world.trigger(
+            ModifyStats::new(creature, StatModifier::Attack(-1))
+                .with_duration(StatDuration::Turns(2))
+                .with_source(effect),
+        );
+        let ability = world.get::<Actions>(effect).unwrap().iter().next().unwrap();
+        let stack = world.get::<AbilityData>(ability).unwrap().0.effects.clone();
+        let mut activation = world.spawn((
+            Activation {
+                ability,
+                caster: effect,
+            },
+            ChildOf(ability),
+        ));
+        let cursor = activation.id();
+        activation.insert((
+            AwaitingChoice {
+                cursor,
+                kind: AwaitingChoiceKind::Entities,
+            },
+            AbilityCursor {
+                stack,
+                context: AbilityContext {
+                    current_target: Some(creature),
+                    ..Default::default()
+                },
+            },
+        ));
+        world
+            .resource_mut::<NextState<TurnState>>()
+            .set(TurnState::AwaitingInputs);
+        settle(&mut app);
+
+        let saved = SavedGame::take(app.world_mut()).unwrap();
+        // The creature weakens the tiles around it as it comes into play.
+        assert_eq!(saved.effects.len(), 10);
+        let burning_index = saved
+            .effects
+            .iter()
+            .position(|saved| saved.effect == burning)
+            .unwrap();
+        assert_eq!(saved.stack.len(), 1);
+        assert_eq!(
+            saved.stack[0].context.current_target,
+            Some(SavedEntity::Card(
+                *app.world().get::<InPlayID>(creature).unwrap()
+            ))
+        );
+        let on_board = saved
+            .cards
+            .iter()
+            .find(|card| matches!(card.location, CardLocation::Board(_)))
+            .unwrap();
+        assert_eq!(
+            on_board.stats.as_ref().unwrap().modifiers[0].source,
+            Some(SavedEntity::Effect(burning_index))
+        );
+        let text = ron::ser::to_string(&saved).unwrap();
+        assert_eq!(ron::de::from_str::<SavedGame>(&text).unwrap(), saved);
+
+        let mut restored = saved.clone().restore().unwrap();
+        settle(&mut restored);
+        assert_eq!(SavedGame::take(restored.world_mut()).unwrap(), saved);
+        // A save file is read in full once its tile effects are registered.
+        let path = std::env::temp_dir().join(format!("game-{}.save.ron", std::process::id()));
+        saved.write(&path).unwrap();
+        let mut from_file = headless_app();
+        SavedGame::restore_file_into(&path, &mut from_file).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut from_file = wait_until_ready(from_file).unwrap();
+        assert_eq!(SavedGame::take(from_file.world_mut()).unwrap(), saved);
+        assert_eq!(
+            GameSnapshot::take(restored.world_mut()),
+            GameSnapshot::take(app.world_mut())
+        );
+
+        // Both games go on the same way, drawing the same cards.
+        for game in [&mut app, &mut restored] {
+            game.world_mut().write_message(ChoiceMade::Cancelled);
+            settle(game);
+            game.world_mut().write_message(EndTurnPressed);
+            settle(game);
+        }
+        let continued = SavedGame::take(app.world_mut()).unwrap();
+        assert_eq!(continued.turn, 2);
+        assert_eq!(SavedGame::take(restored.world_mut()).unwrap(), continued);
+    }
+}
@@ -4,19 +4,26 @@ use bevy::{
     color::Color,
     ecs::{
         component::Component,
-        query::With,
+        entity::Entity,
+        query::{Has, With},
         schedule::{common_conditions::resource_changed, IntoScheduleConfigs},
         system::{Commands, Query, Res, Single},
     },
     math::Vec3,
+    state::state::State,
     sprite::{Anchor, Text2d},
     text::{TextColor, TextFont},
     transform::components::Transform,
 };
 
 use crate::{
-    player::{Player, PlayerResources, TurnPlayer},
+    actions::stack::EffectStack,
+    board::PlayerBase,
+    components::{Health, Owner},
+    player::{Acting, Player, PlayerResources, TurnPlayer},
     renderer::layout::{ScreenLayout, compute_screen_layout_startup},
+    rules::Ruleset,
+    turn_controller::TurnPhase,
 };
 
 #[derive(Component)]
@@ -60,26 +67,55 @@ fn spawn_stats_display(
 
 fn update_stats_display(
     mut text: Single<&mut Text2d, With<StatsText>>,
-    players: Query<(&Player, &PlayerResources)>,
-    turn: Query<&Player, With<TurnPlayer>>,
+    players: Query<(Entity, &Player, &PlayerResources, Has<TurnPlayer>)>,
+    bases: Query<(&Owner, &Health), With<PlayerBase>>,
+    phase: Res<State<TurnPhase>>,
+    stack: Res<EffectStack>,
+    acting: Query<&Player, With<Acting>>,
+    rules: Res<Ruleset>,
 ) {
-    let turn_number = turn.iter().next().map(|player| player.number);
-
-    let mut players: Vec<(&Player, &PlayerResources)> = players.iter().collect();
-    players.sort_by_key(|(player, _)| player.number);
+    let mut players: Vec<_> = players.iter().collect();
+    players.sort_by_key(|(_, player, ..)| player.number);
 
     let mut parts = Vec::new();
-    for (player, resources) in players {
-        let prefix = if Some(player.number) == turn_number {
+    for (entity, player, resources, is_turn) in players {
+        let prefix = if is_turn {
             "▶ "
         } else {
             ""
         };
+        let base = bases
+            .iter()
+            .find(|(owner, _)| owner.0 == entity)
+            .map(|(_, health)| health.value())
+            .unwrap_or(0);
         parts.push(format!(
-            "{prefix}P{}  HP {}/{}  Gold {}",
-            player.number, resources.health, resources.max_health, resources.gold
+            "{prefix}P{}  HP {}/{}  Base {}/{}  Gold {}",
+            player.number,
+            resources.health,
+            resources.max_health,
+            base,
+            rules.base_health,
+            resources.gold
         ));
     }
+
+    if !stack.is_empty()
+        && let Ok(responder) = acting.single()
+    {
+        parts.push(format!(
+            "Stack {}: P{} may respond (Space to pass)",
+            stack.len(),
+            responder.number
+        ));
+    }
+
+    if let TurnPhase::GameOver { winner } = phase.get() {
+        parts.push(match winner {
+            Some(player) => format!("GAME OVER: P{} wins", player.number),
+            None => "GAME OVER: draw".to_string(),
+        });
+    }
     text.0 = parts.join("    ");
 }
 
@@ -1,30 +1,70 @@
 use bevy::{math::U16Vec2, prelude::*};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    actions::execute::{AbilityCursor, AwaitingChoice, AwaitingChoiceKind},
+    actions::{
+        execute::{AbilityCursor, AwaitingChoice, AwaitingChoiceKind},
+        stack::{PassPriority, Priority},
+    },
     board::{
+        combat::AttackRequest,
         movement::MoveRequest,
         tile::{Occupant, Position},
     },
+    board::PlayerBase,
     card::{InHand, OnBoard, Selected},
+    components::{Health, Owner},
     def::effect::EffectDef,
-    player::{Hand, Player, TurnPlayer},
+    mulligan::{resolve_mulligan, select_mulligan_cards},
+    player::{
+        Acting, Hand, Player, PlayerResources, TurnPlayer, discard_to_hand_limit,
+        draw_and_collect_income,
+    },
+    replay::{IntentSent, IntentWriter},
+    rules::Ruleset,
 };
 
+// ============================================================================
+// RESOURCES
+// ============================================================================
+
+/// Number of the turn currently being played, starting at 1 and incremented
+/// every time a new turn starts.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnCounter(pub u32);
+
+impl Default for TurnCounter {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Present while the turn player has ended their turn but end-of-turn
+/// abilities are still resolving. Choices made during that window resume
+/// `TurnState::EndTurn` instead of going back to `Idle`.
+#[derive(Resource, Debug, Default)]
+pub struct TurnEnding;
+
 // ============================================================================
 // STATES
 // ============================================================================
 
-#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TurnPhase {
+    /// Before the first turn, while the players decide which cards of their
+    /// opening hand to redraw.
+    Mulligan,
     Start,
     #[default]
     Main,
     End,
+    /// The game is decided. `winner` is `None` when both players were
+    /// defeated at the same time.
+    GameOver { winner: Option<Player> },
 }
 
-#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
-#[source(TurnPhase = TurnPhase::Main)]
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[source(TurnPhase = TurnPhase::Main | TurnPhase::Mulligan)]
 pub enum TurnState {
     #[default]
     Idle,
@@ -115,7 +155,10 @@ impl Plugin for TurnControllerPlugin {
         app
             // States
             .init_state::<TurnPhase>()
-            .init_state::<TurnState>()
+            .add_sub_state::<TurnState>()
+            .init_resource::<TurnCounter>()
+            .init_resource::<Priority>()
+            .init_resource::<Ruleset>()
             // Raw input messages
             .add_message::<BoardClicked>()
             .add_message::<CardClicked>()
@@ -130,13 +173,15 @@ impl Plugin for TurnControllerPlugin {
             // Commands
             .add_message::<CardPlayRequested>()
             .add_message::<TargetingComplete>()
+            .add_message::<PassPriority>()
+            .add_message::<IntentSent>()
             // Systems
             .add_systems(
                 Update,
                 (
-                    handle_end_turn_input,
-                    handle_end_turn_pressed,
+                    (handle_end_turn_input, handle_end_turn_pressed).chain(),
                     handle_cancel_input,
+                    handle_pass_priority_input,
                     // Routers MUST run before state handlers
                     (card_click_system, board_click_system),
                     handle_idle_state.run_if(in_state(TurnState::Idle)),
@@ -146,23 +191,44 @@ impl Plugin for TurnControllerPlugin {
                         .run_if(in_state(TurnState::AwaitingInputs)),
                     handle_choice_made
                         .run_if(in_state(TurnState::AwaitingInputs)),
-                ),
+                    finish_turn.run_if(in_state(TurnState::EndTurn)),
+                )
+                    .run_if(in_state(TurnPhase::Main)),
             )
             // Cleanup on state exit
             .add_systems(OnExit(TurnState::CardSelected), cleanup_selection)
             .add_systems(OnExit(TurnState::FigureSelected), cleanup_selection)
             .add_systems(OnExit(TurnState::AwaitingInputs), cleanup_selection)
-            .add_systems(OnEnter(TurnState::EndTurn), on_turn_end);
+            .add_systems(
+                OnEnter(TurnState::EndTurn),
+                on_turn_end.run_if(not(resource_exists::<TurnEnding>)),
+            )
+            .add_systems(
+                Update,
+                (select_mulligan_cards, resolve_mulligan)
+                    .run_if(in_state(TurnState::AwaitingInputs))
+                    .run_if(in_state(TurnPhase::Mulligan)),
+            )
+            .add_systems(Update, check_game_over)
+            .add_systems(
+                OnEnter(TurnPhase::End),
+                (discard_to_hand_limit, pass_turn).chain(),
+            )
+            .add_systems(
+                OnEnter(TurnPhase::Start),
+                (start_turn, draw_and_collect_income).chain(),
+            );
     }
 }
 
+/// Enter ends the turn, the same as the end turn button.
 fn handle_end_turn_input(
     keyboard: Res<ButtonInput<KeyCode>>,
-    mut next_state: ResMut<NextState<TurnState>>,
+    mut end_turn: IntentWriter<EndTurnPressed>,
 ) {
     if keyboard.just_pressed(KeyCode::Enter) {
         info!("Enter pressed - ending turn");
-        next_state.set(TurnState::EndTurn);
+        end_turn.write(EndTurnPressed);
     }
 }
 
@@ -181,6 +247,7 @@ fn handle_cancel_input(
     current_state: Res<State<TurnState>>,
     mut next_state: ResMut<NextState<TurnState>>,
     selected: Query<Entity, Or<(With<Selected>, With<Origin>)>>,
+    turn_ending: Option<Res<TurnEnding>>,
     mut commands: Commands,
 ) {
     if keyboard.just_pressed(KeyCode::Escape) {
@@ -190,13 +257,28 @@ fn handle_cancel_input(
         }
         match current_state.get() {
             TurnState::AwaitingInputs | TurnState::CardSelected | TurnState::FigureSelected => {
-                next_state.set(TurnState::Idle);
+                next_state.set(resume_state(turn_ending.is_some()));
             }
             _ => {}
         }
     }
 }
 
+/// Space passes priority for whoever holds it.
+fn handle_pass_priority_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    priority: Res<Priority>,
+    mut passes: IntentWriter<PassPriority>,
+) {
+    if !keyboard.just_pressed(KeyCode::Space) {
+        return;
+    }
+    if let Some(player) = priority.holder() {
+        info!("Space pressed - passing priority");
+        passes.write(PassPriority { player });
+    }
+}
+
 // ============================================================================
 // Router systems (only these read BoardClicked/CardClicked)
 // ============================================================================
@@ -273,8 +355,10 @@ fn handle_idle_state(
     mut next_state: ResMut<NextState<TurnState>>,
     mut commands: Commands,
     occupants: Query<(&Occupant, Entity)>,
-    hand: Single<&Hand, With<TurnPlayer>>,
+    owners: Query<&Owner>,
+    acting: Single<(&Hand, Entity), With<Acting>>,
 ) {
+    let (hand, player) = *acting;
     // Preserve your original priority: card click beats board click.
     // If multiple intents arrive, process in order but return after card selection.
     for intent in intents.read().copied() {
@@ -289,7 +373,10 @@ fn handle_idle_state(
                 return;
             }
             IdleIntent::IdleBoardClick { entity, .. } => {
-                if let Ok((occupant, _)) = occupants.get(entity) {
+                // Only the acting player's own creatures can be picked up.
+                if let Ok((occupant, _)) = occupants.get(entity)
+                    && owners.get(occupant.get()).is_ok_and(|owner| owner.0 == player)
+                {
                     info!("Selected {} transferring to FigureSelected state", entity);
                     commands.entity(occupant.get()).insert(Origin);
                     next_state.set(TurnState::FigureSelected);
@@ -302,9 +389,9 @@ fn handle_idle_state(
 
 fn handle_card_selected(
     mut intents: MessageReader<CardSelectedIntent>,
-    mut play_commands: MessageWriter<CardPlayRequested>,
+    mut play_commands: IntentWriter<CardPlayRequested>,
     mut next_state: ResMut<NextState<TurnState>>,
-    player_hands: Query<(&Hand, &Player), With<TurnPlayer>>,
+    player_hands: Query<(&Hand, &Player), With<Acting>>,
     selected_card: Single<Entity, (With<Selected>, With<InHand>)>,
 ) {
     let Some(&CardSelectedIntent::CardSelectedBoardClick { position, .. }) = intents.read().next()
@@ -336,38 +423,55 @@ fn handle_card_selected(
 
 fn handle_figure_selected(
     mut board_clicks: MessageReader<FigureSelectedBoardClick>,
-    mut play_commands: MessageWriter<MoveRequest>,
+    // Both writers note the order intents were sent in, so only one at a time.
+    mut commands: ParamSet<(IntentWriter<MoveRequest>, IntentWriter<AttackRequest>)>,
     mut next_state: ResMut<NextState<TurnState>>,
-    selected_figure: Query<(Entity, &OnBoard), (With<Origin>, With<OnBoard>)>,
-    tiles: Query<&Position>,
+    selected_figure: Query<(Entity, &OnBoard, &Owner), With<Origin>>,
+    tiles: Query<(&Position, Option<&Occupant>)>,
+    owners: Query<&Owner>,
 ) {
     let Some(FigureSelectedBoardClick {
+        entity: clicked_tile,
         position: next_position,
-        ..
     }) = board_clicks.read().next().copied()
     else {
         return;
     };
 
-    let Ok((entity, &OnBoard { position: tile })) = selected_figure.single() else {
+    let Ok((entity, &OnBoard { position: tile }, owner)) = selected_figure.single() else {
         warn!("FigureSelected state but no Origin entity found");
         next_state.set(TurnState::Idle);
         return;
     };
 
-    let Ok(&Position(from)) = tiles.get(tile) else {
+    let Ok((&Position(from), _)) = tiles.get(tile) else {
         warn!("Could not resolve 'from' position");
         next_state.set(TurnState::Idle);
         return;
     };
 
-    info!("Sending move command from {} to {}", from, next_position);
-
-    play_commands.write(MoveRequest {
-        entity,
-        from,
-        to: next_position,
-    });
+    // Clicking an enemy attacks it, anything else is a move.
+    let enemy = tiles
+        .get(clicked_tile)
+        .ok()
+        .and_then(|(_, occupant)| occupant)
+        .map(Occupant::get)
+        .filter(|&target| owners.get(target).is_ok_and(|o| o != owner));
+
+    if let Some(target) = enemy {
+        info!("Sending attack command from {} on {}", entity, target);
+        commands.p1().write(AttackRequest {
+            attacker: entity,
+            target,
+        });
+    } else {
+        info!("Sending move command from {} to {}", from, next_position);
+        commands.p0().write(MoveRequest {
+            entity,
+            from,
+            to: next_position,
+        });
+    }
     next_state.set(TurnState::Idle);
 }
 
@@ -378,7 +482,7 @@ fn handle_figure_selected(
 fn handle_awaiting_inputs(
     mut board_clicks: MessageReader<AwaitingInputsBoardClick>,
     keyboard: Res<ButtonInput<KeyCode>>,
-    mut choice_made: MessageWriter<ChoiceMade>,
+    mut choice_made: IntentWriter<ChoiceMade>,
 ) {
     // Board click -> select the clicked entity.
     for click in board_clicks.read() {
@@ -412,7 +516,9 @@ fn handle_choice_made(
     mut cursors: Query<&mut AbilityCursor>,
     mut commands: Commands,
     mut next_state: ResMut<NextState<TurnState>>,
+    turn_ending: Option<Res<TurnEnding>>,
 ) {
+    let resume = resume_state(turn_ending.is_some());
     for choice in choices.read() {
         let Some((awaiting_entity, awaiting)) = awaiting.iter().next() else {
             continue;
@@ -425,7 +531,7 @@ fn handle_choice_made(
                     cursor_entity.despawn();
                 }
                 commands.entity(awaiting_entity).remove::<AwaitingChoice>();
-                next_state.set(TurnState::Idle);
+                next_state.set(resume.clone());
                 return;
             }
             ChoiceMade::Option(index) => {
@@ -449,7 +555,7 @@ fn handle_choice_made(
                 cursor.stack.remove(0);
                 cursor.stack.splice(0..0, def.effects.clone());
                 commands.entity(awaiting_entity).remove::<AwaitingChoice>();
-                next_state.set(TurnState::Idle);
+                next_state.set(resume.clone());
                 return;
             }
             ChoiceMade::Entities(entities) => {
@@ -465,7 +571,7 @@ fn handle_choice_made(
                     cursor.context.current_target = Some(first);
                 }
                 commands.entity(awaiting_entity).remove::<AwaitingChoice>();
-                next_state.set(TurnState::Idle);
+                next_state.set(resume.clone());
                 return;
             }
         }
@@ -482,12 +588,224 @@ fn cleanup_selection(
     }
 }
 
-fn on_turn_end(mut next_state: ResMut<NextState<TurnState>>) {
+/// State to return to once an in-progress choice or selection is resolved.
+fn resume_state(turn_ending: bool) -> TurnState {
+    if turn_ending {
+        TurnState::EndTurn
+    } else {
+        TurnState::Idle
+    }
+}
+
+fn on_turn_end(mut commands: Commands) {
     info!("Turn ended");
-    next_state.set(TurnState::Idle);
+    commands.init_resource::<TurnEnding>();
+}
+
+/// Leaves the main phase once every end-of-turn ability has resolved.
+/// Abilities still waiting on a choice at this point were cancelled by the
+/// player and are dropped.
+fn finish_turn(
+    running: Query<(), (With<AbilityCursor>, Without<AwaitingChoice>)>,
+    abandoned: Query<Entity, With<AwaitingChoice>>,
+    mut commands: Commands,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+) {
+    if !running.is_empty() {
+        return;
+    }
+    for entity in &abandoned {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<TurnEnding>();
+    next_phase.set(TurnPhase::End);
+}
+
+/// Hands the turn to the next player in seat order.
+fn pass_turn(
+    mut commands: Commands,
+    players: Query<(Entity, &Player, Has<TurnPlayer>)>,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+) {
+    let mut seats: Vec<_> = players.iter().collect();
+    seats.sort_by_key(|(_, player, _)| player.number);
+
+    if let Some(current) = seats.iter().position(|(_, _, is_turn)| *is_turn) {
+        let (previous, ..) = seats[current];
+        let (next, next_player, _) = seats[(current + 1) % seats.len()];
+        commands.entity(previous).remove::<TurnPlayer>();
+        commands.entity(next).insert(TurnPlayer);
+        info!("Passing turn to player {}", next_player.number);
+    } else {
+        warn!("No turn player found when passing the turn");
+    }
+
+    next_phase.set(TurnPhase::Start);
+}
+
+/// Ends the game as soon as a player's base or life total drops to zero.
+fn check_game_over(
+    phase: Res<State<TurnPhase>>,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+    players: Query<(Entity, &Player, &PlayerResources)>,
+    bases: Query<(&Owner, &Health), With<PlayerBase>>,
+) {
+    if matches!(phase.get(), TurnPhase::GameOver { .. }) {
+        return;
+    }
+
+    let defeated = |player: Entity, resources: &PlayerResources| {
+        resources.health == 0
+            || bases
+                .iter()
+                .any(|(owner, health)| owner.0 == player && health.value() == 0)
+    };
+
+    let (lost, standing): (Vec<_>, Vec<_>) = players
+        .iter()
+        .partition(|(entity, _, resources)| defeated(*entity, resources));
+    if lost.is_empty() {
+        return;
+    }
+
+    let winner = match standing.as_slice() {
+        [(_, player, _)] => Some(**player),
+        _ => None,
+    };
+    info!("Game over, winner: {:?}", winner);
+    next_phase.set(TurnPhase::GameOver { winner });
+}
+
+fn start_turn(mut counter: ResMut<TurnCounter>, mut next_phase: ResMut<NextState<TurnPhase>>) {
+    counter.0 += 1;
+    info!("Starting turn {}", counter.0);
+    next_phase.set(TurnPhase::Main);
 }
 
 /// Call this when starting a new turn
 pub fn reset_turn(mut next_state: ResMut<NextState<TurnState>>) {
     next_state.set(TurnState::Idle);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GameRng, actions::ActionPlugin, card::InDeck, events::GameMessagesPlugin,
+        player::PlayerBundle,
+    };
+    use bevy::state::app::StatesPlugin;
+
+    #[test]
+    fn ending_the_turn_passes_it_to_the_other_player() {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let first = app.world_mut().spawn((Player { number: 0 }, TurnPlayer)).id();
+        let second = app.world_mut().spawn(Player { number: 1 }).id();
+        app.update();
+
+        for expected in [second, first] {
+            app.world_mut()
+                .resource_mut::<NextState<TurnState>>()
+                .set(TurnState::EndTurn);
+            for _ in 0..5 {
+                app.update();
+            }
+
+            assert!(app.world().get::<TurnPlayer>(expected).is_some());
+            assert_eq!(
+                app.world().resource::<State<TurnPhase>>().get(),
+                &TurnPhase::Main
+            );
+        }
+
+        assert_eq!(app.world().resource::<TurnCounter>().0, 3);
+        assert!(app.world().get_resource::<TurnEnding>().is_none());
+    }
+
+    #[test]
+    fn turns_start_with_a_draw_and_income_and_end_at_the_hand_limit() {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            GameMessagesPlugin,
+            ActionPlugin,
+            TurnControllerPlugin,
+        ))
+        .init_resource::<GameRng>()
+        .insert_resource(ButtonInput::<KeyCode>::default())
+        .insert_resource(Ruleset {
+            max_hand_size: 2,
+            ..Default::default()
+        });
+
+        let first = app
+            .world_mut()
+            .spawn((Player { number: 0 }, PlayerBundle::default(), TurnPlayer))
+            .id();
+        let second = app
+            .world_mut()
+            .spawn((Player { number: 1 }, PlayerBundle::default()))
+            .id();
+        for _ in 0..3 {
+            app.world_mut()
+                .spawn((InHand { parent: first }, Owner(first)));
+            app.world_mut()
+                .spawn((InDeck { parent: second }, Owner(second)));
+        }
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<NextState<TurnState>>()
+            .set(TurnState::EndTurn);
+        for _ in 0..5 {
+            app.update();
+        }
+
+        let hand_size = |app: &App, player| app.world().get::<Hand>(player).unwrap().len();
+        assert_eq!(hand_size(&app, first), 2);
+        assert_eq!(hand_size(&app, second), 1);
+        assert_eq!(
+            app.world().get::<PlayerResources>(second).unwrap().gold,
+            PlayerResources::default().gold + 2
+        );
+    }
+
+    #[test]
+    fn destroyed_base_ends_the_game() {
+        let mut app = App::new();
+        app.add_plugins((StatesPlugin, TurnControllerPlugin))
+            .insert_resource(ButtonInput::<KeyCode>::default());
+
+        let loser = app
+            .world_mut()
+            .spawn((Player { number: 0 }, PlayerResources::default(), TurnPlayer))
+            .id();
+        app.world_mut()
+            .spawn((Player { number: 1 }, PlayerResources::default()));
+        let base = app
+            .world_mut()
+            .spawn((PlayerBase, Health(10), Owner(loser)))
+            .id();
+        app.update();
+
+        app.world_mut().get_mut::<Health>(base).unwrap().set_value(0);
+        app.update();
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<State<TurnPhase>>().get(),
+            &TurnPhase::GameOver {
+                winner: Some(Player { number: 1 })
+            }
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Taking back moves within a turn. Every move remembers where the creature
+//! stood and the movement points it had, and undoing puts both back. Only
+//! moves can be taken back, and only until the turn player commits to
+//! something else or the game turns up something new: a drawn card, a
+//! revealed trap, a triggered ability or a random number.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    GameRng,
+    actions::{execute::AbilityCursor, stack::PassPriority, timing::ActionTiming},
+    board::{
+        combat::AttackRequest,
+        movement::{MoveRequest, handle_movement},
+    },
+    card::{CurrentMovementPoints, OnBoard},
+    events::{CardMoved, CardsDrawn, TrapRevealed},
+    replay::{IntentSent, IntentWriter},
+    turn_controller::{CardPlayRequested, ChoiceMade, EndTurnPressed, TurnPhase},
+};
+
+/// A creature as it stood before it moved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MadeMove {
+    creature: Entity,
+    tile: Entity,
+    movement_points: u16,
+    /// The game's randomness at the time, to tell whether it was used since.
+    rng: GameRng,
+}
+
+/// The moves of the turn that can still be taken back, the latest last.
+#[derive(Resource, Debug, Default)]
+pub struct UndoStack {
+    moves: Vec<MadeMove>,
+    /// Requested moves, kept once they are made.
+    pending: Vec<MadeMove>,
+}
+
+impl UndoStack {
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.moves.clear();
+        self.pending.clear();
+    }
+}
+
+/// Takes back the last move of the turn.
+#[derive(Message)]
+pub struct UndoPressed;
+
+/// Ctrl+Z takes back the last move.
+fn handle_undo_input(keyboard: Res<ButtonInput<KeyCode>>, mut undo: IntentWriter<UndoPressed>) {
+    let control = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if control && keyboard.just_pressed(KeyCode::KeyZ) {
+        info!("Ctrl+Z pressed - undoing the last move");
+        undo.write(UndoPressed);
+    }
+}
+
+/// Remembers where a creature stands before a requested move is made.
+fn remember_move(
+    mut requests: MessageReader<MoveRequest>,
+    creatures: Query<(&OnBoard, &CurrentMovementPoints)>,
+    rng: Res<GameRng>,
+    mut stack: ResMut<UndoStack>,
+) {
+    for request in requests.read() {
+        let Ok((on_board, movement)) = creatures.get(request.entity) else {
+            continue;
+        };
+        stack.pending.push(MadeMove {
+            creature: request.entity,
+            tile: on_board.position,
+            movement_points: movement.0,
+            rng: rng.clone(),
+        });
+    }
+}
+
+/// Puts the creature of the last move back where it stood.
+fn undo(
+    mut pressed: MessageReader<UndoPressed>,
+    mut stack: ResMut<UndoStack>,
+    mut creatures: Query<&mut CurrentMovementPoints>,
+    mut commands: Commands,
+) {
+    for _ in pressed.read() {
+        let Some(made) = stack.moves.pop() else {
+            info!("Nothing to undo");
+            continue;
+        };
+        let Ok(mut movement) = creatures.get_mut(made.creature) else {
+            warn!("The creature of the move to undo is gone");
+            continue;
+        };
+        movement.0 = made.movement_points;
+        commands.entity(made.creature).insert(OnBoard {
+            position: made.tile,
+        });
+    }
+}
+
+type StartedAbility = Or<(Added<AbilityCursor>, Added<ActionTiming>)>;
+
+/// Messages after which the turn cannot be taken back: the players commit
+/// to something other than a move, or hidden cards come to light.
+#[derive(SystemParam)]
+pub struct Commitments<'w, 's> {
+    plays: MessageReader<'w, 's, CardPlayRequested>,
+    attacks: MessageReader<'w, 's, AttackRequest>,
+    choices: MessageReader<'w, 's, ChoiceMade>,
+    passes: MessageReader<'w, 's, PassPriority>,
+    end_turns: MessageReader<'w, 's, EndTurnPressed>,
+    draws: MessageReader<'w, 's, CardsDrawn>,
+    reveals: MessageReader<'w, 's, TrapRevealed>,
+    /// Abilities a move set off change more than the move itself.
+    started: Query<'w, 's, (), StartedAbility>,
+}
+
+impl Commitments<'_, '_> {
+    fn any(&mut self) -> bool {
+        let counts = [
+            self.plays.read().count(),
+            self.attacks.read().count(),
+            self.choices.read().count(),
+            self.passes.read().count(),
+            self.end_turns.read().count(),
+            self.draws.read().count(),
+            self.reveals.read().count(),
+        ];
+        counts.iter().any(|&count| count > 0) || !self.started.is_empty()
+    }
+}
+
+/// Keeps the moves that were made, and forgets every move once the turn
+/// cannot be taken back.
+fn track_moves(
+    mut moved: MessageReader<CardMoved>,
+    mut commitments: Commitments,
+    rng: Res<GameRng>,
+    mut stack: ResMut<UndoStack>,
+) {
+    let mut pending = std::mem::take(&mut stack.pending);
+    for moved in moved.read() {
+        if let Some(index) = pending.iter().position(|made| made.creature == moved.card) {
+            stack.moves.push(pending.remove(index));
+        }
+    }
+    // Undoing would draw the same random numbers again.
+    let rolled = stack.moves.last().is_some_and(|made| made.rng != *rng);
+    if commitments.any() || rolled {
+        stack.clear();
+    }
+}
+
+fn clear_undo_stack(mut stack: ResMut<UndoStack>) {
+    stack.clear();
+}
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UndoStack>()
+            .add_message::<UndoPressed>()
+            .add_message::<IntentSent>()
+            .add_systems(
+                Update,
+                (
+                    (handle_undo_input, undo).chain(),
+                    remember_move.before(handle_movement),
+                )
+                    .run_if(in_state(TurnPhase::Main)),
+            )
+            .add_systems(PostUpdate, track_moves)
+            .add_systems(OnExit(TurnPhase::Main), clear_undo_stack);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::{BoardRes, tile::Occupant},
+        replay::{GameLog, Intent, settle, tests::game_with_creature_in_play},
+        save::SavedGame,
+    };
+    use bevy::math::U16Vec2;
+    use rand::RngCore;
+
+    #[test]
+    fn moves_are_taken_back_until_a_random_number_is_drawn() {
+        let (mut app, creature) = game_with_creature_in_play(11);
+
+        let from = U16Vec2::new(3, 6);
+        let to = U16Vec2::new(4, 6);
+        let tile_of = |app: &App, position| {
+            app.world()
+                .resource::<BoardRes>()
+                .get_tile(&position)
+                .unwrap()
+        };
+        let step = |app: &mut App| {
+            app.world_mut().write_message(MoveRequest {
+                entity: creature,
+                from,
+                to,
+            });
+            settle(app);
+            assert_eq!(
+                app.world().get::<OnBoard>(creature).unwrap().position,
+                tile_of(app, to)
+            );
+        };
+
+        app.world_mut()
+            .get_mut::<CurrentMovementPoints>(creature)
+            .unwrap()
+            .0 = 2;
+        let before = SavedGame::take(app.world_mut()).unwrap();
+        step(&mut app);
+        assert_eq!(app.world().resource::<UndoStack>().len(), 1);
+        app.world_mut().write_message(UndoPressed);
+        settle(&mut app);
+        assert_eq!(SavedGame::take(app.world_mut()).unwrap(), before);
+        assert_eq!(
+            app.world().get::<OnBoard>(creature).unwrap().position,
+            tile_of(&app, from)
+        );
+        assert!(app.world().get::<Occupant>(tile_of(&app, to)).is_none());
+        assert!(app.world().resource::<UndoStack>().is_empty());
+        assert!(
+            app.world()
+                .resource::<GameLog>()
+                .intents
+                .contains(&Intent::Undo)
+        );
+
+        // Once the game has drawn a random number, the move stays.
+        step(&mut app);
+        app.world_mut().resource_mut::<GameRng>().0.next_u32();
+        settle(&mut app);
+        app.world_mut().write_message(UndoPressed);
+        settle(&mut app);
+        assert_eq!(
+            app.world().get::<OnBoard>(creature).unwrap().position,
+            tile_of(&app, to)
+        );
+    }
+}